@@ -0,0 +1,279 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    held_pickups_to_js_held_pickups, item_id_to_js_item_id, js_item_id_to_item_id,
+    pickups_to_js_recipe, BasicCrafter, Crafter, CrafterError, HeldPickups, InternalItemId,
+    InternalPickups, ItemId, Pickup, PickupIterator, Recipe, SlotMap,
+};
+
+const BAG_SIZE: usize = 8;
+
+/// Follows a Bag of Crafting through a run: pickups lying on the floor, the order pickups went into
+/// the bag and what gets pushed out when a ninth one is collected.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct BagSimulator {
+    crafter: BasicCrafter,
+    bag: VecDeque<Pickup>,
+    floor: SlotMap<Pickup, u8>,
+}
+
+#[wasm_bindgen]
+impl BagSimulator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        itempools_xml: &str,
+        items_metadata_xml: &str,
+    ) -> Result<BagSimulator, CrafterError> {
+        Ok(BagSimulator::with_crafter(BasicCrafter::new(
+            itempools_xml,
+            items_metadata_xml,
+        )?))
+    }
+
+    /// A pickup appeared on the floor.
+    pub fn drop_pickup(&mut self, pickup: Pickup) {
+        self.floor[pickup] = self.floor[pickup].saturating_add(1);
+    }
+
+    /// A pickup on the floor was removed without going in the bag (the player walked over it, it
+    /// despawned, ...).
+    pub fn remove_floor_pickup(&mut self, pickup: Pickup) {
+        self.floor[pickup] = self.floor[pickup].saturating_sub(1);
+    }
+
+    /// Swing the bag at a pickup on the floor. Returns the pickup that got pushed out of the bag,
+    /// if it was already full.
+    pub fn collect(&mut self, pickup: Pickup) -> Result<Option<Pickup>, CrafterError> {
+        if self.floor[pickup] == 0 {
+            return Err(CrafterError::NotOnFloor(pickup));
+        }
+        self.floor[pickup] -= 1;
+        Ok(self.push(pickup))
+    }
+
+    /// Craft the item the bag currently holds, emptying it.
+    pub fn craft(&mut self) -> Result<ItemId, CrafterError> {
+        let item_id = self
+            .craft_item()
+            .ok_or(CrafterError::BagNotFull(self.bag.len()))?;
+        Ok(item_id_to_js_item_id(item_id))
+    }
+
+    /// What the bag would craft right now, if it is full.
+    pub fn result(&self) -> Option<ItemId> {
+        self.current_result().map(item_id_to_js_item_id)
+    }
+
+    /// The bag contents, oldest first.
+    pub fn bag(&self) -> Recipe {
+        let contents: Vec<Pickup> = self.contents().collect();
+        pickups_to_js_recipe(contents.as_slice())
+    }
+
+    pub fn floor(&self) -> Result<HeldPickups, JsValue> {
+        held_pickups_to_js_held_pickups(&self.floor)
+    }
+
+    /// The shortest list of floor pickups to collect for the bag to craft `item`, if there is one.
+    pub fn plan(&self, item: ItemId) -> Result<Option<Recipe>, CrafterError> {
+        let item_id = js_item_id_to_item_id(item)?;
+        Ok(self
+            .plan_collection(item_id)
+            .map(|pickups| pickups_to_js_recipe(pickups.as_slice())))
+    }
+
+    pub fn reset(&mut self) {
+        self.bag.clear();
+        self.floor.iter_mut().for_each(|(_, c)| *c = 0);
+    }
+}
+
+impl BagSimulator {
+    pub fn with_crafter(crafter: BasicCrafter) -> Self {
+        BagSimulator {
+            crafter,
+            bag: VecDeque::with_capacity(BAG_SIZE),
+            floor: Default::default(),
+        }
+    }
+
+    /// Put a pickup straight in the bag. Returns the pickup that got pushed out, if any.
+    pub fn push(&mut self, pickup: Pickup) -> Option<Pickup> {
+        let evicted = if self.bag.len() == BAG_SIZE {
+            self.bag.pop_front()
+        } else {
+            None
+        };
+        self.bag.push_back(pickup);
+        evicted
+    }
+
+    pub fn contents(&self) -> impl Iterator<Item = Pickup> + '_ {
+        self.bag.iter().copied()
+    }
+
+    pub fn floor_count(&self, pickup: Pickup) -> u8 {
+        self.floor[pickup]
+    }
+
+    pub fn current_result(&self) -> Option<InternalItemId> {
+        self.full_bag().map(|pickups| self.crafter.craft(pickups))
+    }
+
+    pub fn craft_item(&mut self) -> Option<InternalItemId> {
+        let item_id = self.current_result()?;
+        self.bag.clear();
+        Some(item_id)
+    }
+
+    pub fn plan_collection(&self, target: InternalItemId) -> Option<Vec<Pickup>> {
+        let bag: Vec<Pickup> = self.contents().collect();
+        plan_collection(&self.crafter, bag.as_slice(), &self.floor, target)
+    }
+
+    fn full_bag(&self) -> Option<InternalPickups> {
+        if self.bag.len() != BAG_SIZE {
+            return None;
+        }
+        let mut pickups = [Pickup::RedHeart; BAG_SIZE];
+        for (slot, pickup) in pickups.iter_mut().zip(self.bag.iter()) {
+            *slot = *pickup;
+        }
+        Some(InternalPickups::new(pickups))
+    }
+}
+
+/// Find the fewest pickups to collect from `floor` so that a bag currently holding `bag` (oldest
+/// first) crafts `target`.
+///
+/// Collecting a pickup into a full bag pushes out the oldest one, so after collecting `k` pickups the
+/// bag holds the newest `8 - k` of its current contents plus everything collected. The order the
+/// collected pickups go in doesn't change the result, and collecting more than 8 only pushes out
+/// pickups collected earlier, so every `k` up to 8 is tried from smallest to largest.
+fn plan_collection<C: Crafter>(
+    crafter: &C,
+    bag: &[Pickup],
+    floor: &SlotMap<Pickup, u8>,
+    target: InternalItemId,
+) -> Option<Vec<Pickup>> {
+    let min_collected = BAG_SIZE.saturating_sub(bag.len());
+    for collected in min_collected..=BAG_SIZE {
+        let kept = &bag[bag.len() - (BAG_SIZE - collected)..];
+        for mut pickups in PickupIterator::new(floor.clone(), collected as u8) {
+            for (slot, pickup) in pickups.iter_mut().skip(collected).zip(kept.iter()) {
+                *slot = *pickup;
+            }
+            if crafter.craft(InternalPickups::new(pickups)) == target {
+                return Some(pickups[..collected].to_vec());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use Pickup::*;
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+
+    fn get_bag_simulator() -> BagSimulator {
+        BagSimulator::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap()
+    }
+
+    #[test]
+    fn evicts_oldest() {
+        let mut bag = get_bag_simulator();
+        for _ in 0..8 {
+            bag.drop_pickup(SoulHeart);
+            assert_eq!(None, bag.current_result());
+            assert_eq!(None, bag.collect(SoulHeart).unwrap());
+        }
+        assert_eq!(Some(InternalItemId(114)), bag.current_result());
+
+        bag.drop_pickup(EternalHeart);
+        bag.drop_pickup(LuckyPenny);
+        assert_eq!(Some(SoulHeart), bag.collect(EternalHeart).unwrap());
+        assert_eq!(Some(SoulHeart), bag.collect(LuckyPenny).unwrap());
+        assert_eq!(
+            vec![
+                SoulHeart,
+                SoulHeart,
+                SoulHeart,
+                SoulHeart,
+                SoulHeart,
+                SoulHeart,
+                EternalHeart,
+                LuckyPenny
+            ],
+            bag.contents().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(InternalItemId(363)), bag.current_result());
+    }
+
+    #[test]
+    fn craft_empties_bag() {
+        let mut bag = get_bag_simulator();
+        for p in [
+            RedHeart, RedHeart, SoulHeart, Penny, Penny, Nickel, LuckyPenny,
+        ]
+        .iter()
+        {
+            bag.push(*p);
+        }
+        assert_eq!(None, bag.craft_item());
+
+        bag.drop_pickup(Key);
+        bag.drop_pickup(Key);
+        bag.collect(Key).unwrap();
+        assert_eq!(1, bag.floor_count(Key));
+        assert_eq!(Some(InternalItemId(26)), bag.craft_item());
+        assert_eq!(0, bag.contents().count());
+        assert_eq!(None, bag.current_result());
+    }
+
+    #[test]
+    fn plan_with_eviction() {
+        let mut bag = get_bag_simulator();
+        for p in [
+            Penny, Bomb, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart,
+        ]
+        .iter()
+        {
+            bag.push(*p);
+        }
+        for p in [EternalHeart, LuckyPenny, Key, Key].iter() {
+            bag.drop_pickup(*p);
+        }
+        // the penny and the bomb have to be pushed out, which takes two pickups
+        let plan = bag.plan_collection(InternalItemId(363)).unwrap();
+        assert_eq!(2, plan.len());
+
+        for p in plan.iter().copied() {
+            bag.collect(p).unwrap();
+        }
+        assert_eq!(Some(InternalItemId(363)), bag.current_result());
+    }
+
+    #[test]
+    fn plan_already_full() {
+        let mut bag = get_bag_simulator();
+        for _ in 0..8 {
+            bag.push(SoulHeart);
+        }
+        assert_eq!(Some(vec![]), bag.plan_collection(InternalItemId(114)));
+    }
+
+    #[test]
+    fn plan_impossible() {
+        let mut bag = get_bag_simulator();
+        bag.drop_pickup(SoulHeart);
+        assert_eq!(None, bag.plan_collection(InternalItemId(114)));
+    }
+}
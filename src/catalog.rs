@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{js_item_id_to_item_id, CrafterError, InternalItemId, ItemId};
+
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ItemType {
+    Passive,
+    Active,
+    Familiar,
+}
+
+/// A collectible as described by the game's items.xml.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CatalogItem {
+    pub id: InternalItemId,
+    pub name: String,
+    pub description: String,
+    pub item_type: ItemType,
+    /// Only active items have charges.
+    pub max_charges: Option<u32>,
+    /// The sprite, relative to the game's resources folder.
+    pub gfx_path: String,
+    /// The achievement that unlocks the item, `None` if it's unlocked from the start.
+    pub achievement: Option<u16>,
+}
+
+/// Every collectible of the game's items.xml, trinkets and null items aren't included.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ItemCatalog {
+    items: BTreeMap<InternalItemId, CatalogItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemsXml {
+    #[serde(default = "default_gfx_root")]
+    gfxroot: String,
+    #[serde(rename = "$value")]
+    entries: Vec<ItemsXmlEntry>,
+}
+
+fn default_gfx_root() -> String {
+    "gfx/items/".to_owned()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ItemsXmlEntry {
+    Passive(Collectible),
+    Active(Collectible),
+    Familiar(Collectible),
+    Trinket(Other),
+    Null(Other),
+}
+
+#[derive(Debug, Deserialize)]
+struct Collectible {
+    id: InternalItemId,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    gfx: String,
+    maxcharges: Option<u32>,
+    achievement: Option<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Other {}
+
+#[wasm_bindgen]
+impl ItemCatalog {
+    #[wasm_bindgen(constructor)]
+    pub fn new(items_xml: &str) -> Result<ItemCatalog, CrafterError> {
+        let items: ItemsXml = serde_xml_rs::from_reader(items_xml.as_bytes())
+            .map_err(|e| CrafterError::from_xml("items.xml", e))?;
+        let mut catalog = ItemCatalog::default();
+        for entry in items.entries {
+            let (item_type, collectible) = match entry {
+                ItemsXmlEntry::Passive(c) => (ItemType::Passive, c),
+                ItemsXmlEntry::Active(c) => (ItemType::Active, c),
+                ItemsXmlEntry::Familiar(c) => (ItemType::Familiar, c),
+                ItemsXmlEntry::Trinket(_) | ItemsXmlEntry::Null(_) => continue,
+            };
+            catalog.items.insert(
+                collectible.id,
+                CatalogItem {
+                    id: collectible.id,
+                    name: collectible.name,
+                    description: collectible.description,
+                    item_type,
+                    max_charges: collectible.maxcharges,
+                    gfx_path: format!("{}collectibles/{}", items.gfxroot, collectible.gfx),
+                    achievement: collectible.achievement,
+                },
+            );
+        }
+        Ok(catalog)
+    }
+
+    pub fn name(&self, item: ItemId) -> Result<Option<String>, CrafterError> {
+        Ok(self.get_js(item)?.map(|item| item.name.clone()))
+    }
+
+    pub fn description(&self, item: ItemId) -> Result<Option<String>, CrafterError> {
+        Ok(self.get_js(item)?.map(|item| item.description.clone()))
+    }
+
+    pub fn item_type(&self, item: ItemId) -> Result<Option<ItemType>, CrafterError> {
+        Ok(self.get_js(item)?.map(|item| item.item_type))
+    }
+
+    pub fn max_charges(&self, item: ItemId) -> Result<Option<u32>, CrafterError> {
+        Ok(self.get_js(item)?.and_then(|item| item.max_charges))
+    }
+
+    pub fn gfx_path(&self, item: ItemId) -> Result<Option<String>, CrafterError> {
+        Ok(self.get_js(item)?.map(|item| item.gfx_path.clone()))
+    }
+
+    pub fn achievement(&self, item: ItemId) -> Result<Option<u16>, CrafterError> {
+        Ok(self.get_js(item)?.and_then(|item| item.achievement))
+    }
+}
+
+impl ItemCatalog {
+    pub fn get(&self, item: InternalItemId) -> Option<&CatalogItem> {
+        self.items.get(&item)
+    }
+
+    /// Sorted by id.
+    pub fn items(&self) -> impl Iterator<Item = &CatalogItem> {
+        self.items.values()
+    }
+
+    fn get_js(&self, item: ItemId) -> Result<Option<&CatalogItem>, CrafterError> {
+        Ok(self.get(js_item_id_to_item_id(item)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS_XML: &str = include_str!("../fixtures/items.xml");
+
+    #[test]
+    fn parse() {
+        let catalog = ItemCatalog::new(ITEMS_XML).unwrap();
+        assert_eq!(
+            vec![1, 8, 33, 67, 105, 182],
+            catalog
+                .items()
+                .map(|item| u16::from(item.id))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(&CatalogItem {
+                id: InternalItemId(33),
+                name: "The Bible".to_owned(),
+                description: "Reusable bombs".to_owned(),
+                item_type: ItemType::Active,
+                max_charges: Some(4),
+                gfx_path: "gfx/items/collectibles/Collectibles_033_TheBible.png".to_owned(),
+                achievement: Some(12),
+            }),
+            catalog.get(InternalItemId(33))
+        );
+
+        let sad_onion = catalog.get(InternalItemId(1)).unwrap();
+        assert_eq!("The Sad Onion", sad_onion.name);
+        assert_eq!(ItemType::Passive, sad_onion.item_type);
+        assert_eq!(None, sad_onion.max_charges);
+        assert_eq!(None, sad_onion.achievement);
+        assert_eq!(
+            ItemType::Familiar,
+            catalog.get(InternalItemId(67)).unwrap().item_type
+        );
+        assert_eq!(None, catalog.get(InternalItemId(2)));
+    }
+}
@@ -0,0 +1,74 @@
+use strum_macros::EnumIter;
+use wasm_bindgen::prelude::*;
+
+use crate::ItemTags;
+
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, EnumIter)]
+pub enum Character {
+    #[default]
+    Isaac,
+    Magdalene,
+    Cain,
+    Judas,
+    BlueBaby,
+    Eve,
+    Samson,
+    Azazel,
+    Lazarus,
+    Eden,
+    TheLost,
+    Lilith,
+    Keeper,
+    Apollyon,
+    TheForgotten,
+    Bethany,
+    JacobAndEsau,
+    TaintedIsaac,
+    TaintedMagdalene,
+    TaintedCain,
+    TaintedJudas,
+    TaintedBlueBaby,
+    TaintedEve,
+    TaintedSamson,
+    TaintedAzazel,
+    TaintedLazarus,
+    TaintedEden,
+    TaintedLost,
+    TaintedLilith,
+    TaintedKeeper,
+    TaintedApollyon,
+    TaintedForgotten,
+    TaintedBethany,
+    TaintedJacob,
+}
+
+impl Character {
+    /// Whether an item with `tags` can come out of a pool while playing this character.
+    pub(crate) fn allows(self, tags: ItemTags, birthright: bool) -> bool {
+        match self {
+            Character::TaintedLost => tags.contains(ItemTags::OFFENSIVE),
+            Character::TheLost if birthright => !tags.contains(ItemTags::NO_LOST_BR),
+            Character::Keeper | Character::TaintedKeeper => !tags.contains(ItemTags::NO_KEEPER),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows() {
+        let defensive = ItemTags::SUMMONABLE;
+        let offensive = ItemTags::OFFENSIVE | ItemTags::NO_LOST_BR;
+        assert!(Character::Isaac.allows(defensive, false));
+        assert!(!Character::TaintedLost.allows(defensive, false));
+        assert!(Character::TaintedLost.allows(offensive, false));
+        assert!(Character::TheLost.allows(offensive, false));
+        assert!(!Character::TheLost.allows(offensive, true));
+        assert!(!Character::Keeper.allows(ItemTags::NO_KEEPER, false));
+        assert!(Character::Keeper.allows(offensive, false));
+    }
+}
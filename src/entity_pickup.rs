@@ -0,0 +1,147 @@
+use std::ops::RangeInclusive;
+
+use crate::Pickup;
+use crate::Pickup::*;
+
+const HEART: u32 = 10;
+const COIN: u32 = 20;
+const KEY: u32 = 30;
+const BOMB: u32 = 40;
+const POOP: u32 = 42;
+const PILL: u32 = 70;
+const LIL_BATTERY: u32 = 90;
+const TAROT_CARD: u32 = 300;
+
+const GOLDEN_PILL: u32 = 14;
+const HORSE_PILL_FLAG: u32 = 0x800;
+
+struct EntityPickup {
+    variant: u32,
+    subtypes: RangeInclusive<u32>,
+    components: &'static [(Pickup, u8)],
+}
+
+const fn entity(
+    variant: u32,
+    subtypes: RangeInclusive<u32>,
+    components: &'static [(Pickup, u8)],
+) -> EntityPickup {
+    EntityPickup {
+        variant,
+        subtypes,
+        components,
+    }
+}
+
+/// What each in-game pickup entity puts in the bag. The first matching row wins.
+const ENTITY_PICKUPS: [EntityPickup; 42] = [
+    entity(HEART, 1..=1, &[(RedHeart, 2)]),
+    entity(HEART, 2..=2, &[(RedHeart, 1)]),
+    entity(HEART, 3..=3, &[(SoulHeart, 2)]),
+    entity(HEART, 4..=4, &[(EternalHeart, 1)]),
+    entity(HEART, 5..=5, &[(RedHeart, 2)]),
+    entity(HEART, 6..=6, &[(BlackHeart, 1)]),
+    entity(HEART, 7..=7, &[(GoldHeart, 1)]),
+    entity(HEART, 8..=8, &[(SoulHeart, 1)]),
+    entity(HEART, 9..=9, &[(RedHeart, 2)]),
+    entity(HEART, 10..=10, &[(RedHeart, 1), (SoulHeart, 1)]),
+    entity(HEART, 11..=11, &[(BoneHeart, 1)]),
+    entity(HEART, 12..=12, &[(RottenHeart, 1)]),
+    entity(COIN, 1..=1, &[(Penny, 1)]),
+    entity(COIN, 2..=2, &[(Nickel, 1)]),
+    entity(COIN, 3..=3, &[(Dime, 1)]),
+    entity(COIN, 4..=4, &[(Penny, 2)]),
+    entity(COIN, 5..=5, &[(LuckyPenny, 1)]),
+    entity(COIN, 6..=6, &[(Nickel, 1)]),
+    entity(COIN, 7..=7, &[(GoldenPenny, 1)]),
+    entity(KEY, 1..=1, &[(Key, 1)]),
+    entity(KEY, 2..=2, &[(GoldenKey, 1)]),
+    entity(KEY, 3..=3, &[(Key, 2)]),
+    entity(KEY, 4..=4, &[(ChargedKey, 1)]),
+    entity(BOMB, 1..=1, &[(Bomb, 1)]),
+    entity(BOMB, 2..=2, &[(Bomb, 2)]),
+    entity(BOMB, 4..=4, &[(GoldenBomb, 1)]),
+    entity(BOMB, 7..=7, &[(GigaBomb, 1)]),
+    entity(POOP, 0..=0, &[(PoopNugget, 1)]),
+    entity(PILL, GOLDEN_PILL..=GOLDEN_PILL, &[(GoldenPill, 1)]),
+    entity(
+        PILL,
+        (GOLDEN_PILL | HORSE_PILL_FLAG)..=(GOLDEN_PILL | HORSE_PILL_FLAG),
+        &[(GoldenPill, 1)],
+    ),
+    entity(PILL, 1..=(HORSE_PILL_FLAG | 0x7ff), &[(Pill, 1)]),
+    entity(LIL_BATTERY, 1..=1, &[(LilBattery, 1)]),
+    entity(LIL_BATTERY, 2..=2, &[(MicroBattery, 1)]),
+    entity(LIL_BATTERY, 3..=3, &[(MegaBattery, 1)]),
+    entity(LIL_BATTERY, 4..=4, &[(GoldenBattery, 1)]),
+    entity(TAROT_CARD, 32..=41, &[(Rune, 1)]),
+    entity(TAROT_CARD, 49..=49, &[(DiceShard, 1)]),
+    entity(TAROT_CARD, 55..=55, &[(Rune, 1)]),
+    entity(TAROT_CARD, 78..=78, &[(CrackedKey, 1)]),
+    entity(TAROT_CARD, 81..=97, &[(Rune, 1)]),
+    entity(TAROT_CARD, 1..=31, &[(Card, 1)]),
+    entity(TAROT_CARD, 42..=u32::MAX, &[(Card, 1)]),
+];
+
+/// Look up the crafting components a pickup entity (`EntityType.ENTITY_PICKUP`, identified by
+/// its variant and subtype) contributes to the bag, with how many of each it adds.
+///
+/// Returns `None` for entities that can't be picked up by the bag, like chests or troll bombs.
+pub fn entity_pickup_components(variant: u32, subtype: u32) -> Option<&'static [(Pickup, u8)]> {
+    ENTITY_PICKUPS
+        .iter()
+        .find(|e| e.variant == variant && e.subtypes.contains(&subtype))
+        .map(|e| e.components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hearts() {
+        assert_eq!(Some(&[(RedHeart, 1)][..]), entity_pickup_components(10, 2));
+        assert_eq!(Some(&[(RedHeart, 2)][..]), entity_pickup_components(10, 5));
+        assert_eq!(
+            Some(&[(RedHeart, 1), (SoulHeart, 1)][..]),
+            entity_pickup_components(10, 10)
+        );
+    }
+
+    #[test]
+    fn coins() {
+        assert_eq!(Some(&[(Penny, 2)][..]), entity_pickup_components(20, 4));
+        assert_eq!(Some(&[(Nickel, 1)][..]), entity_pickup_components(20, 6));
+        assert_eq!(
+            Some(&[(GoldenPenny, 1)][..]),
+            entity_pickup_components(20, 7)
+        );
+    }
+
+    #[test]
+    fn pills_and_cards() {
+        assert_eq!(Some(&[(Pill, 1)][..]), entity_pickup_components(70, 3));
+        assert_eq!(Some(&[(Pill, 1)][..]), entity_pickup_components(70, 0x803));
+        assert_eq!(
+            Some(&[(GoldenPill, 1)][..]),
+            entity_pickup_components(70, 14)
+        );
+        assert_eq!(Some(&[(Card, 1)][..]), entity_pickup_components(300, 1));
+        assert_eq!(Some(&[(Rune, 1)][..]), entity_pickup_components(300, 36));
+        assert_eq!(
+            Some(&[(DiceShard, 1)][..]),
+            entity_pickup_components(300, 49)
+        );
+        assert_eq!(
+            Some(&[(CrackedKey, 1)][..]),
+            entity_pickup_components(300, 78)
+        );
+    }
+
+    #[test]
+    fn not_collectable() {
+        assert_eq!(None, entity_pickup_components(40, 3));
+        assert_eq!(None, entity_pickup_components(50, 0));
+        assert_eq!(None, entity_pickup_components(300, 0));
+    }
+}
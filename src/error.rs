@@ -0,0 +1,296 @@
+use std::fmt::{Display, Formatter};
+
+use wasm_bindgen::prelude::*;
+use xml::common::Position;
+
+use crate::{GameVersion, InternalItemId, ItemPool, Pickup, SaveFileError, SeedParseError};
+
+/// Why game data or crafting rules couldn't be loaded, or why the crafter can't do what it was
+/// asked to.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CrafterError {
+    /// `file` isn't well formed xml. Lines and columns count from 1.
+    XmlSyntax {
+        file: &'static str,
+        line: u64,
+        column: u64,
+        message: String,
+    },
+    /// `file` is well formed xml, but not laid out like the game's file.
+    Xml { file: &'static str, message: String },
+    /// itempools.xml has a pool the game doesn't.
+    UnknownPool(String),
+    /// itempools.xml lists the same pool twice.
+    DuplicatePool(ItemPool),
+    /// A pool in itempools.xml lists the same item twice.
+    DuplicatePoolItem {
+        pool: ItemPool,
+        item: InternalItemId,
+    },
+    /// items_metadata.xml lists the same item twice.
+    DuplicateItem(InternalItemId),
+    /// An item in items_metadata.xml has no quality.
+    MissingQuality(InternalItemId),
+    /// Something that should be an item id isn't one.
+    InvalidItemId(String),
+    /// A mod's file refers to an item by a name no mod item has.
+    UnknownItemName(String),
+    /// The crafting rules json doesn't describe crafting rules.
+    Rules(String),
+    /// The save file an unlock profile is read from can't be parsed.
+    SaveFile(SaveFileError),
+    /// A seed string that isn't a valid seed.
+    Seed(SeedParseError),
+    /// An in-game pickup entity that doesn't go in the bag.
+    NotABagPickup { variant: u32, subtype: u32 },
+    /// The bag simulator was told to collect a pickup that isn't on the floor.
+    NotOnFloor(Pickup),
+    /// The bag simulator was told to craft with fewer than 8 pickups in the bag.
+    BagNotFull(usize),
+    /// A file bundled with the crate for `version` doesn't decompress to text.
+    BundledData {
+        version: GameVersion,
+        file: &'static str,
+        message: String,
+    },
+}
+
+impl CrafterError {
+    pub(crate) fn from_xml(file: &'static str, error: serde_xml_rs::Error) -> Self {
+        match error {
+            serde_xml_rs::Error::Syntax { source } => {
+                let position = source.position();
+                CrafterError::XmlSyntax {
+                    file,
+                    line: position.row + 1,
+                    column: position.column + 1,
+                    message: source.msg().to_owned(),
+                }
+            }
+            error => CrafterError::Xml {
+                file,
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+impl Display for CrafterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrafterError::XmlSyntax {
+                file,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "error parsing {} at {}:{}: {}",
+                file, line, column, message
+            ),
+            CrafterError::Xml { file, message } => write!(f, "error parsing {}: {}", file, message),
+            CrafterError::UnknownPool(name) => write!(f, "unknown item pool {:?}", name),
+            CrafterError::DuplicatePool(pool) => {
+                write!(f, "the {} pool is listed twice", pool.name())
+            }
+            CrafterError::DuplicatePoolItem { pool, item } => write!(
+                f,
+                "item {} is listed twice in the {} pool",
+                u16::from(*item),
+                pool.name()
+            ),
+            CrafterError::DuplicateItem(item) => write!(
+                f,
+                "item {} is listed twice in the item metadata",
+                u16::from(*item)
+            ),
+            CrafterError::MissingQuality(item) => {
+                write!(f, "item {} has no quality", u16::from(*item))
+            }
+            CrafterError::InvalidItemId(id) => write!(f, "{} is not a valid item id", id),
+            CrafterError::UnknownItemName(name) => write!(f, "no mod item is named {:?}", name),
+            CrafterError::Rules(message) => write!(f, "error parsing crafting rules: {}", message),
+            CrafterError::SaveFile(error) => error.fmt(f),
+            CrafterError::Seed(error) => error.fmt(f),
+            CrafterError::NotABagPickup { variant, subtype } => {
+                write!(f, "pickup {}.{} can't be put in the bag", variant, subtype)
+            }
+            CrafterError::NotOnFloor(pickup) => {
+                write!(f, "there is no {:?} on the floor", pickup)
+            }
+            CrafterError::BagNotFull(len) => {
+                write!(f, "the bag needs 8 pickups to craft, it only holds {}", len)
+            }
+            CrafterError::BundledData {
+                version,
+                file,
+                message,
+            } => write!(
+                f,
+                "bundled {} of {:?} is corrupt: {}",
+                file, version, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CrafterError {}
+
+impl From<SaveFileError> for CrafterError {
+    fn from(error: SaveFileError) -> Self {
+        CrafterError::SaveFile(error)
+    }
+}
+
+impl From<SeedParseError> for CrafterError {
+    fn from(error: SeedParseError) -> Self {
+        CrafterError::Seed(error)
+    }
+}
+
+impl From<CrafterError> for JsValue {
+    fn from(error: CrafterError) -> Self {
+        JsValue::from(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BagSimulator, BasicCrafter, CraftingRules, DeltaCrafter, UnlockProfile};
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+
+    fn itempools_error(itempools_xml: &str) -> CrafterError {
+        BasicCrafter::new(itempools_xml, ITEMS_METADATA_XML).unwrap_err()
+    }
+
+    fn items_metadata_error(items_metadata_xml: &str) -> CrafterError {
+        BasicCrafter::new(ITEMPOOLS_XML, items_metadata_xml).unwrap_err()
+    }
+
+    #[test]
+    fn xml_syntax() {
+        let error = itempools_error("<ItemPools>\n  <Pool Name=\"treasure\">\n  </Poo>\n");
+        match error {
+            CrafterError::XmlSyntax {
+                file, line, column, ..
+            } => {
+                assert_eq!("itempools.xml", file);
+                assert_eq!((3, 8), (line, column));
+            }
+            error => panic!("expected a syntax error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn unknown_pool() {
+        assert_eq!(
+            CrafterError::UnknownPool("moddedPool".to_owned()),
+            itempools_error(
+                r#"<ItemPools><Pool Name="moddedPool"><Item Id="1" Weight="1"/></Pool></ItemPools>"#
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_item() {
+        assert_eq!(
+            CrafterError::DuplicatePool(ItemPool::Boss),
+            itempools_error(
+                r#"<ItemPools><Pool Name="boss"><Item Id="14" Weight="1"/></Pool><Pool Name="boss"><Item Id="15" Weight="1"/></Pool></ItemPools>"#
+            )
+        );
+        assert_eq!(
+            CrafterError::DuplicatePoolItem {
+                pool: ItemPool::Boss,
+                item: InternalItemId(14),
+            },
+            itempools_error(
+                r#"<ItemPools><Pool Name="boss"><Item Id="14" Weight="1"/><Item Id="14" Weight="1"/></Pool></ItemPools>"#
+            )
+        );
+        assert_eq!(
+            CrafterError::DuplicateItem(InternalItemId(1)),
+            items_metadata_error(
+                r#"<items><item id="1" quality="3"/><item id="1" quality="2"/></items>"#
+            )
+        );
+    }
+
+    #[test]
+    fn missing_quality() {
+        assert_eq!(
+            CrafterError::MissingQuality(InternalItemId(2)),
+            items_metadata_error(r#"<items><item id="1" quality="3"/><item id="2"/></items>"#)
+        );
+    }
+
+    #[test]
+    fn invalid_item_id() {
+        assert_eq!(
+            CrafterError::InvalidItemId("sad onion".to_owned()),
+            items_metadata_error(r#"<items><item id="sad onion" quality="3"/></items>"#)
+        );
+        assert_eq!(
+            CrafterError::InvalidItemId("70000".to_owned()),
+            itempools_error(
+                r#"<ItemPools><Pool Name="treasure"><Item Id="70000" Weight="1"/></Pool></ItemPools>"#
+            )
+        );
+    }
+
+    #[test]
+    fn constructors() {
+        assert!(matches!(
+            DeltaCrafter::new("<ItemPools>", ITEMS_METADATA_XML),
+            Err(CrafterError::XmlSyntax { .. })
+        ));
+        assert!(matches!(
+            BagSimulator::new(ITEMPOOLS_XML, "<items><item id=\"1\"/></items>"),
+            Err(CrafterError::MissingQuality(_))
+        ));
+        assert_eq!(
+            Err(CrafterError::SaveFile(SaveFileError::Truncated {
+                needed: 16,
+                len: 3
+            })),
+            UnlockProfile::from_save_file(b"ISA", "<items/>").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn crafting() {
+        let mut delta_crafter = DeltaCrafter::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        assert_eq!(
+            Err(CrafterError::Seed(SeedParseError::Length(3))),
+            delta_crafter.set_seed_string("ABC")
+        );
+        assert_eq!(
+            Err(CrafterError::NotABagPickup {
+                variant: 10,
+                subtype: 99
+            }),
+            delta_crafter.add_entity_pickup(10, 99)
+        );
+
+        let mut bag = BagSimulator::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        assert_eq!(
+            Err(CrafterError::NotOnFloor(Pickup::Penny)),
+            bag.collect(Pickup::Penny)
+        );
+        bag.drop_pickup(Pickup::Penny);
+        assert_eq!(Ok(None), bag.collect(Pickup::Penny));
+        assert!(matches!(bag.craft(), Err(CrafterError::BagNotFull(1))));
+    }
+
+    #[test]
+    fn rules() {
+        assert!(matches!(
+            CraftingRules::from_json("{}"),
+            Err(CrafterError::Rules(_))
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    get_items_metadata, get_pool_item_weights, item_id_to_js_item_id, CrafterError, InternalItemId,
+    ItemId, ItemMetadata, ItemPool, PoolItem,
+};
+
+/// The item pools and item metadata a crafter is built from: the base game's itempools.xml and
+/// items_metadata.xml, with the files of any mods merged on top.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct GameData {
+    pub(crate) pools: HashMap<ItemPool, HashMap<InternalItemId, PoolItem>>,
+    pub(crate) metadata: HashMap<InternalItemId, ItemMetadata>,
+    /// The ids given to the items of mods, by name.
+    pub(crate) mod_items: HashMap<String, InternalItemId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModItemsXml {
+    #[serde(rename = "$value", default)]
+    entries: Vec<ModItemsXmlEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ModItemsXmlEntry {
+    Passive(ModItem),
+    Active(ModItem),
+    Familiar(ModItem),
+    Trinket(Other),
+    Null(Other),
+}
+
+#[derive(Debug, Deserialize)]
+struct ModItem {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Other {}
+
+#[wasm_bindgen]
+impl GameData {
+    #[wasm_bindgen(constructor)]
+    pub fn new(itempools_xml: &str, items_metadata_xml: &str) -> Result<GameData, CrafterError> {
+        let mut data = GameData {
+            pools: HashMap::new(),
+            metadata: HashMap::new(),
+            mod_items: HashMap::new(),
+        };
+        data.merge_itempools(itempools_xml)?;
+        data.merge_items_metadata(items_metadata_xml)?;
+        Ok(data)
+    }
+
+    /// Number the collectibles of a mod's `content/items.xml`. Mods don't pick ids for their items:
+    /// like the game, they're numbered in file order after the largest id loaded so far, and the
+    /// mod's other files refer to them by name. Merge a mod's items.xml before its other files.
+    pub fn merge_items(&mut self, items_xml: &str) -> Result<(), CrafterError> {
+        let items: ModItemsXml = serde_xml_rs::from_reader(items_xml.as_bytes())
+            .map_err(|e| CrafterError::from_xml("items.xml", e))?;
+        let mut next = u16::from(self.largest_item_id()) as u32 + 1;
+        for entry in items.entries {
+            let item = match entry {
+                ModItemsXmlEntry::Passive(item)
+                | ModItemsXmlEntry::Active(item)
+                | ModItemsXmlEntry::Familiar(item) => item,
+                ModItemsXmlEntry::Trinket(_) | ModItemsXmlEntry::Null(_) => continue,
+            };
+            let id = u16::try_from(next)
+                .map(InternalItemId::from)
+                .map_err(|_| CrafterError::InvalidItemId(next.to_string()))?;
+            next += 1;
+            // a later mod's files mean its own item
+            self.mod_items.insert(item.name, id);
+        }
+        Ok(())
+    }
+
+    /// Merge a mod's `content/itempools.xml`. Like the game does, the mod's items are added to the
+    /// pool of the same name, and an item the pool already has gets the mod's entry instead.
+    /// Entries refer to base game items by `Id` and to mod items by `Name`.
+    pub fn merge_itempools(&mut self, itempools_xml: &str) -> Result<(), CrafterError> {
+        for (pool, items) in get_pool_item_weights(itempools_xml, &self.mod_items)? {
+            self.pools.entry(pool).or_default().extend(items);
+        }
+        Ok(())
+    }
+
+    /// Merge a mod's `content/items_metadata.xml`, its entries replace those of the same item.
+    /// Entries refer to base game items by `id` and to mod items by `name`.
+    pub fn merge_items_metadata(&mut self, items_metadata_xml: &str) -> Result<(), CrafterError> {
+        self.metadata.extend(
+            get_items_metadata(items_metadata_xml, &self.mod_items)?
+                .into_iter()
+                .map(|item| (item.id, item)),
+        );
+        Ok(())
+    }
+
+    /// The largest item id in any pool, in the metadata or given to a mod item.
+    pub fn largest_item(&self) -> ItemId {
+        item_id_to_js_item_id(self.largest_item_id())
+    }
+}
+
+impl GameData {
+    pub fn largest_item_id(&self) -> InternalItemId {
+        self.pools
+            .values()
+            .flat_map(|items| items.keys())
+            .chain(self.metadata.keys())
+            .chain(self.mod_items.values())
+            .copied()
+            .max()
+            .unwrap_or(InternalItemId(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BasicCrafter, DeltaCrafter, ItemTags, Pickup::*};
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+    const MOD_ITEMS_XML: &str = include_str!("../fixtures/mod/content/items.xml");
+    const MOD_ITEMPOOLS_XML: &str = include_str!("../fixtures/mod/content/itempools.xml");
+    const MOD_ITEMS_METADATA_XML: &str = include_str!("../fixtures/mod/content/items_metadata.xml");
+
+    fn get_modded_data() -> GameData {
+        let mut data = GameData::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        data.merge_items(MOD_ITEMS_XML).unwrap();
+        data.merge_itempools(MOD_ITEMPOOLS_XML).unwrap();
+        data.merge_items_metadata(MOD_ITEMS_METADATA_XML).unwrap();
+        data
+    }
+
+    #[test]
+    fn merge() {
+        let base = GameData::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        let data = get_modded_data();
+        assert_eq!(InternalItemId(729), base.largest_item_id());
+        // mod items are numbered after the base game's, trinkets have ids of their own
+        let (lucky_onion, brother_rock, onion_juice) = (
+            InternalItemId(730),
+            InternalItemId(731),
+            InternalItemId(732),
+        );
+        assert_eq!(onion_juice, data.largest_item_id());
+
+        let treasure = &data.pools[&ItemPool::Treasure];
+        assert_eq!(base.pools[&ItemPool::Treasure].len() + 2, treasure.len());
+        assert_eq!(5., treasure[&lucky_onion].weight);
+        assert_eq!(1., treasure[&brother_rock].weight);
+        assert_eq!(1., data.pools[&ItemPool::Devil][&brother_rock].weight);
+        assert_eq!(3., treasure[&InternalItemId(1)].weight);
+        assert_eq!(
+            base.pools[&ItemPool::Shop].len(),
+            data.pools[&ItemPool::Shop].len()
+        );
+
+        assert_eq!(4, data.metadata[&lucky_onion].quality);
+        assert_eq!(2, data.metadata[&onion_juice].quality);
+        assert_eq!(0, data.metadata[&InternalItemId(1)].quality);
+        assert_eq!(
+            base.metadata[&InternalItemId(2)].quality,
+            data.metadata[&InternalItemId(2)].quality
+        );
+    }
+
+    #[test]
+    fn craft_modded() {
+        let crafter = BasicCrafter::from_game_data(&get_modded_data());
+        assert_eq!(InternalItemId(732), crafter.largest_item());
+        assert_eq!(4, crafter.item_quality(InternalItemId(730)));
+        assert!(crafter
+            .item_tags(InternalItemId(730))
+            .contains(ItemTags::OFFENSIVE));
+
+        let mut delta_crafter = DeltaCrafter::with_crafter(crafter);
+        for pickup in [RedHeart, SoulHeart, Penny, Key, Bomb, Card].iter() {
+            for _ in 0..8 {
+                delta_crafter.add_pickup(*pickup);
+            }
+        }
+        assert!(!delta_crafter.methods[InternalItemId(730)].is_empty());
+    }
+
+    #[test]
+    fn second_mod() {
+        let mut data = get_modded_data();
+        data.merge_items(r#"<items><passive name="Lucky Onion"/></items>"#)
+            .unwrap();
+        assert_eq!(InternalItemId(733), data.largest_item_id());
+        assert_eq!(
+            Some(&InternalItemId(733)),
+            data.mod_items.get("Lucky Onion")
+        );
+    }
+
+    #[test]
+    fn unknown_item_name() {
+        let mut data = get_modded_data();
+        assert_eq!(
+            Err(CrafterError::UnknownItemName("Sad Onion".to_owned())),
+            data.merge_itempools(
+                r#"<ItemPools><Pool Name="boss"><Item Name="Sad Onion" Weight="1"/></Pool></ItemPools>"#
+            )
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use strum_macros::EnumIter;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "bundled-data")]
+use crate::CrafterError;
+use crate::CraftingRules;
+
+/// A game patch whose item pools, item metadata and crafting rules the crate knows.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, EnumIter)]
+pub enum GameVersion {
+    /// Repentance 1.7.x, the version the web app ships with.
+    #[default]
+    Repentance,
+}
+
+impl GameVersion {
+    pub fn rules(self) -> CraftingRules {
+        match self {
+            GameVersion::Repentance => CraftingRules::default(),
+        }
+    }
+}
+
+#[cfg(feature = "bundled-data")]
+impl GameVersion {
+    pub fn itempools_xml(self) -> Result<String, CrafterError> {
+        self.inflate(
+            "itempools.xml",
+            match self {
+                GameVersion::Repentance => include_bytes!("../data/repentance/itempools.xml.zz"),
+            },
+        )
+    }
+
+    pub fn items_metadata_xml(self) -> Result<String, CrafterError> {
+        self.inflate(
+            "items_metadata.xml",
+            match self {
+                GameVersion::Repentance => {
+                    include_bytes!("../data/repentance/items_metadata.xml.zz")
+                }
+            },
+        )
+    }
+
+    /// The bundled files are the game's xml files compressed as zlib streams.
+    fn inflate(self, file: &'static str, data: &[u8]) -> Result<String, CrafterError> {
+        let error = |message: String| CrafterError::BundledData {
+            version: self,
+            file,
+            message,
+        };
+        let data = miniz_oxide::inflate::decompress_to_vec_zlib(data)
+            .map_err(|e| error(format!("{:?}", e.status)))?;
+        String::from_utf8(data).map_err(|e| error(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "bundled-data"))]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn repentance_matches_web_assets() {
+        assert_eq!(
+            include_str!("../web/assets/itempools.xml"),
+            GameVersion::Repentance.itempools_xml().unwrap()
+        );
+        assert_eq!(
+            include_str!("../web/assets/items_metadata.xml"),
+            GameVersion::Repentance.items_metadata_xml().unwrap()
+        );
+    }
+
+    #[test]
+    fn corrupt_data() {
+        match GameVersion::Repentance.inflate("itempools.xml", b"not zlib") {
+            Err(CrafterError::BundledData { version, file, .. }) => {
+                assert_eq!((GameVersion::Repentance, "itempools.xml"), (version, file))
+            }
+            result => panic!("expected a bundled data error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn every_version_parses() {
+        for version in GameVersion::iter() {
+            crate::BasicCrafter::for_version(version).unwrap();
+        }
+    }
+}
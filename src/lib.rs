@@ -2,15 +2,59 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
 use std::marker::PhantomData;
-use std::ops::{Index, IndexMut, RangeInclusive};
+use std::ops::{BitOr, Index, IndexMut, RangeInclusive};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smartstring::{LazyCompact, SmartString};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{EnumCount, EnumIter};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
+use pool_state::PoolItem;
+use roll_table::RollTables;
+
+pub use bag::BagSimulator;
+pub use catalog::{CatalogItem, ItemCatalog, ItemType};
+pub use character::Character;
+pub use entity_pickup::entity_pickup_components;
+pub use error::CrafterError;
+pub use game_data::GameData;
+pub use game_version::GameVersion;
+pub use localization::{Localization, StringTable};
+pub use modifier::{BuiltinModifier, Chaos, RollContext, RunModifier, SacredOrb};
+pub use planner::{CraftPlan, PlannedCraft};
+pub use pool_state::PoolState;
+pub use recipe_diff::{ItemChange, RecipeChange, RecipeDiff};
+pub use recipe_index::{recipe_at, recipe_index, recipes, RECIPE_COUNT};
+pub use rules::{CraftingRules, PickupWeight, PoolRule, QualityBound, QualityOffset};
+pub use save_file::{AchievementTable, SaveFile, SaveFileError, SaveVersion};
+pub use seed::{format_seed, parse_seed, SeedParseError};
+pub use unlocks::UnlockProfile;
+pub use validate::{validate_game_data, validate_mod_data, Diagnostic};
+
+mod bag;
+mod catalog;
+mod character;
+mod entity_pickup;
+mod error;
+mod game_data;
+mod game_version;
+mod localization;
+mod modifier;
+mod planner;
+mod pool_state;
+mod recipe_diff;
+mod recipe_index;
+mod roll_table;
+mod rules;
+mod save_file;
+mod seed;
+mod unlocks;
+mod validate;
+
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
@@ -19,7 +63,20 @@ pub fn set_panic_hook() {
 }
 
 #[wasm_bindgen]
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, EnumIter)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    EnumCount,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
 pub enum Pickup {
     RedHeart,
     SoulHeart,
@@ -46,6 +103,10 @@ pub enum Pickup {
     Rune,
     DiceShard,
     CrackedKey,
+    GoldenPenny,
+    GoldenPill,
+    GoldenBattery,
+    PoopNugget,
 }
 
 impl Pickup {
@@ -77,6 +138,10 @@ impl Pickup {
             Rune => 4,
             DiceShard => 4,
             CrackedKey => 2,
+            GoldenPenny => 7,
+            GoldenPill => 7,
+            GoldenBattery => 7,
+            PoopNugget => 0,
         }
     }
 
@@ -108,13 +173,19 @@ impl Pickup {
             Rune => (0x00000003, 0x00000005, 0x00000019),
             DiceShard => (0x00000003, 0x00000007, 0x0000001D),
             CrackedKey => (0x00000003, 0x0000000D, 0x00000007),
+            GoldenPenny => (0x00000003, 0x00000017, 0x00000019),
+            GoldenPill => (0x00000003, 0x00000019, 0x00000018),
+            GoldenBattery => (0x00000003, 0x0000001B, 0x0000000B),
+            PoopNugget => (0x00000004, 0x00000003, 0x00000011),
         }
     }
 }
 
-impl Slotable for Pickup {
+impl Slotable for Pickup {}
+
+impl FixedSlotable for Pickup {
     fn largest() -> Self {
-        Pickup::CrackedKey
+        Pickup::PoopNugget
     }
 }
 
@@ -147,6 +218,10 @@ impl From<usize> for Pickup {
             22 => Rune,
             23 => DiceShard,
             24 => CrackedKey,
+            25 => GoldenPenny,
+            26 => GoldenPill,
+            27 => GoldenBattery,
+            28 => PoopNugget,
             _ => panic!(),
         }
     }
@@ -181,6 +256,10 @@ impl From<Pickup> for usize {
             Rune => 22,
             DiceShard => 23,
             CrackedKey => 24,
+            GoldenPenny => 25,
+            GoldenPill => 26,
+            GoldenBattery => 27,
+            PoopNugget => 28,
         }
     }
 }
@@ -195,6 +274,12 @@ const TS_APPEND_RECIPE: &'static str = r#"
 type Recipe = Array<Pickup>;
 "#;
 
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_PLANNED_CRAFT: &'static str = r#"
+type PlannedCraft = { item: ItemId, recipe: Recipe };
+type CraftPlan = { crafts: Array<PlannedCraft>, complete: boolean };
+"#;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "Record<Pickup, number>")]
@@ -206,20 +291,21 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "Array<ItemId>")]
     pub type ItemIds;
 
+    #[wasm_bindgen(typescript_type = "Recipe")]
+    pub type Recipe;
+
     #[wasm_bindgen(typescript_type = "Array<Recipe>")]
     pub type Recipes;
+
+    #[wasm_bindgen(typescript_type = "Record<ItemId, number>")]
+    pub type ItemValues;
+
+    #[wasm_bindgen(typescript_type = "CraftPlan")]
+    pub type JsCraftPlan;
 }
 
-const QUALITY_BOUNDS_LIST: [(u32, RangeInclusive<u32>); 8] = [
-    (34, 4..=4),
-    (30, 3..=4),
-    (26, 2..=4),
-    (22, 1..=4),
-    (18, 1..=3),
-    (14, 1..=2),
-    (8, 0..=2),
-    (0, 0..=1),
-];
+/// The seed the bag starts crafting from in the unseeded game.
+pub const DEFAULT_SEED: u32 = 0x77777770;
 
 struct Rng {
     seed: u32,
@@ -228,14 +314,18 @@ struct Rng {
 
 impl Default for Rng {
     fn default() -> Self {
+        Rng::new(DEFAULT_SEED)
+    }
+}
+
+impl Rng {
+    fn new(seed: u32) -> Self {
         Rng {
-            seed: 0x77777770,
+            seed,
             shifts: (0, 0, 0),
         }
     }
-}
 
-impl Rng {
     fn next(&mut self) -> u32 {
         let mut num = self.seed;
         num ^= num >> self.shifts.0;
@@ -297,9 +387,16 @@ impl From<InternalItemId> for usize {
     }
 }
 
-impl Slotable for InternalItemId {
-    fn largest() -> Self {
-        InternalItemId(730)
+impl Slotable for InternalItemId {}
+
+impl FromStr for InternalItemId {
+    type Err = CrafterError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        id.trim()
+            .parse()
+            .map(InternalItemId)
+            .map_err(|_| CrafterError::InvalidItemId(id.to_owned()))
     }
 }
 
@@ -321,13 +418,29 @@ struct Pool {
 #[derive(Debug, Deserialize)]
 struct Item {
     #[serde(rename = "Id")]
-    id: InternalItemId,
+    id: Option<SmartString<LazyCompact>>,
+    /// Mods refer to their own items by name.
+    #[serde(rename = "Name")]
+    name: Option<SmartString<LazyCompact>>,
     #[serde(rename = "Weight")]
     weight: f32,
+    #[serde(rename = "DecreaseBy", default = "default_decrease_by")]
+    decrease_by: f32,
+    #[serde(rename = "RemoveOn", default = "default_remove_on")]
+    remove_on: f32,
+}
+
+fn default_decrease_by() -> f32 {
+    1.
+}
+
+fn default_remove_on() -> f32 {
+    0.1
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumIter)]
-enum ItemPool {
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum ItemPool {
     Treasure,
     Shop,
     Boss,
@@ -362,6 +475,19 @@ enum ItemPool {
 }
 
 impl ItemPool {
+    fn is_greed(self) -> bool {
+        matches!(
+            self,
+            ItemPool::GreedTreasure
+                | ItemPool::GreedBoss
+                | ItemPool::GreedShop
+                | ItemPool::GreedCurse
+                | ItemPool::GreedDevil
+                | ItemPool::GreedAngel
+                | ItemPool::GreedSecret
+        )
+    }
+
     fn name(self) -> &'static str {
         match self {
             ItemPool::Treasure => "treasure",
@@ -399,51 +525,234 @@ impl ItemPool {
     }
 }
 
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, EnumIter)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    Greed,
+    Greedier,
+}
+
+impl GameMode {
+    fn is_greed(self) -> bool {
+        match self {
+            GameMode::Normal => false,
+            GameMode::Greed | GameMode::Greedier => true,
+        }
+    }
+
+    /// The pool actually rolled from in this mode when crafting calls for `pool`.
+    fn pool(self, pool: ItemPool) -> ItemPool {
+        if !self.is_greed() {
+            return pool;
+        }
+        match pool {
+            ItemPool::Treasure => ItemPool::GreedTreasure,
+            ItemPool::Shop => ItemPool::GreedShop,
+            ItemPool::Boss => ItemPool::GreedBoss,
+            ItemPool::Devil => ItemPool::GreedDevil,
+            ItemPool::Angel => ItemPool::GreedAngel,
+            ItemPool::Secret => ItemPool::GreedSecret,
+            ItemPool::Curse => ItemPool::GreedCurse,
+            _ => pool,
+        }
+    }
+}
+
+/// Find the id of an item in one of the game files, either given directly or, for items added by
+/// mods, through the name in `names`.
+fn resolve_item_id(
+    id: Option<&str>,
+    name: Option<&str>,
+    names: &HashMap<String, InternalItemId>,
+) -> Result<InternalItemId, CrafterError> {
+    match (id, name) {
+        (Some(id), _) => id.parse(),
+        (None, Some(name)) => names
+            .get(name)
+            .copied()
+            .ok_or_else(|| CrafterError::UnknownItemName(name.to_owned())),
+        (None, None) => Err(CrafterError::InvalidItemId(String::new())),
+    }
+}
+
 fn get_pool_item_weights(
     itempools_xml: &str,
-) -> Result<HashMap<ItemPool, HashMap<InternalItemId, f32>>, JsValue> {
+    names: &HashMap<String, InternalItemId>,
+) -> Result<HashMap<ItemPool, HashMap<InternalItemId, PoolItem>>, CrafterError> {
     let pools: ItemPools = serde_xml_rs::from_reader(itempools_xml.as_bytes())
-        .map_err(|e| JsValue::from(format!("error parsing itempools xml: {}", e)))?;
+        .map_err(|e| CrafterError::from_xml("itempools.xml", e))?;
     let item_pool_lookup: HashMap<&'static str, ItemPool> =
         ItemPool::iter().map(|pool| (pool.name(), pool)).collect();
-    Ok(pools
-        .pools
-        .into_iter()
-        .map(|pool| {
-            (
-                item_pool_lookup.get(pool.name.as_str()).copied().unwrap(),
-                pool.items
-                    .into_iter()
-                    .map(|item| (item.id, item.weight))
-                    .collect(),
-            )
-        })
-        .collect())
+    let mut result = HashMap::new();
+    for pool in pools.pools {
+        let item_pool = item_pool_lookup
+            .get(pool.name.as_str())
+            .copied()
+            .ok_or_else(|| CrafterError::UnknownPool(pool.name.to_string()))?;
+        let mut items = HashMap::new();
+        for item in pool.items {
+            let id = resolve_item_id(item.id.as_deref(), item.name.as_deref(), names)?;
+            let entry = PoolItem {
+                weight: item.weight,
+                decrease_by: item.decrease_by,
+                remove_on: item.remove_on,
+            };
+            if items.insert(id, entry).is_some() {
+                return Err(CrafterError::DuplicatePoolItem {
+                    pool: item_pool,
+                    item: id,
+                });
+            }
+        }
+        if result.insert(item_pool, items).is_some() {
+            return Err(CrafterError::DuplicatePool(item_pool));
+        }
+    }
+    Ok(result)
 }
 
 #[derive(Debug, Deserialize)]
 struct ItemsMetadata {
     #[serde(rename = "item")]
-    items: Vec<ItemMetadata>,
+    items: Vec<ItemMetadataXml>,
 }
 
 #[derive(Debug, Deserialize)]
+struct ItemMetadataXml {
+    id: Option<SmartString<LazyCompact>>,
+    name: Option<SmartString<LazyCompact>>,
+    quality: Option<u32>,
+    #[serde(default)]
+    tags: String,
+}
+
+#[derive(Debug, Clone)]
 struct ItemMetadata {
     id: InternalItemId,
     quality: u32,
+    tags: String,
 }
 
-fn get_item_qualities(items_metadata_xml: &str) -> Result<SlotMap<InternalItemId, u32>, JsValue> {
+fn get_items_metadata(
+    items_metadata_xml: &str,
+    names: &HashMap<String, InternalItemId>,
+) -> Result<Vec<ItemMetadata>, CrafterError> {
     let metadata: ItemsMetadata = serde_xml_rs::from_reader(items_metadata_xml.as_bytes())
-        .map_err(|e| JsValue::from(format!("error parsing item metadata xml: {}", e)))?;
-    let mut result = SlotMap::default();
+        .map_err(|e| CrafterError::from_xml("items_metadata.xml", e))?;
+    let mut seen = HashSet::new();
+    let mut result = Vec::with_capacity(metadata.items.len());
     for item in metadata.items {
-        result[item.id] = item.quality;
+        let id = resolve_item_id(item.id.as_deref(), item.name.as_deref(), names)?;
+        if !seen.insert(id) {
+            return Err(CrafterError::DuplicateItem(id));
+        }
+        result.push(ItemMetadata {
+            id,
+            quality: item.quality.ok_or(CrafterError::MissingQuality(id))?,
+            tags: item.tags,
+        });
     }
     Ok(result)
 }
 
-trait Slotable: Clone + From<usize> + Into<usize> {
+/// The `tags` of an item in items_metadata.xml, as a bitset.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ItemTags(u32);
+
+impl ItemTags {
+    pub const OFFENSIVE: ItemTags = ItemTags(1 << 0);
+    pub const SUMMONABLE: ItemTags = ItemTags(1 << 1);
+    pub const NO_LOST_BR: ItemTags = ItemTags(1 << 2);
+    pub const NO_CANTRIP: ItemTags = ItemTags(1 << 3);
+    pub const MONSTER_MANUAL: ItemTags = ItemTags(1 << 4);
+    pub const TEARS_UP: ItemTags = ItemTags(1 << 5);
+    pub const BABY: ItemTags = ItemTags(1 << 6);
+    pub const STARS: ItemTags = ItemTags(1 << 7);
+    pub const FLY: ItemTags = ItemTags(1 << 8);
+    pub const MOM: ItemTags = ItemTags(1 << 9);
+    pub const ANGEL: ItemTags = ItemTags(1 << 10);
+    pub const SPIDER: ItemTags = ItemTags(1 << 11);
+    pub const BOOK: ItemTags = ItemTags(1 << 12);
+    pub const QUEST: ItemTags = ItemTags(1 << 13);
+    pub const NO_GREED: ItemTags = ItemTags(1 << 14);
+    pub const DEAD: ItemTags = ItemTags(1 << 15);
+    pub const UNIQUE_FAMILIAR: ItemTags = ItemTags(1 << 16);
+    pub const POOP: ItemTags = ItemTags(1 << 17);
+    pub const DEVIL: ItemTags = ItemTags(1 << 18);
+    pub const MUSHROOM: ItemTags = ItemTags(1 << 19);
+    pub const SYRINGE: ItemTags = ItemTags(1 << 20);
+    pub const FOOD: ItemTags = ItemTags(1 << 21);
+    pub const TECH: ItemTags = ItemTags(1 << 22);
+    pub const GUPPY: ItemTags = ItemTags(1 << 23);
+    pub const BATTERY: ItemTags = ItemTags(1 << 24);
+    pub const BOB: ItemTags = ItemTags(1 << 25);
+    pub const NO_KEEPER: ItemTags = ItemTags(1 << 26);
+    pub const NO_CHALLENGE: ItemTags = ItemTags(1 << 27);
+    pub const NO_DAILY: ItemTags = ItemTags(1 << 28);
+    pub const NO_EDEN: ItemTags = ItemTags(1 << 29);
+
+    fn from_name(name: &str) -> Option<ItemTags> {
+        Some(match name {
+            "offensive" => ItemTags::OFFENSIVE,
+            "summonable" => ItemTags::SUMMONABLE,
+            "nolostbr" => ItemTags::NO_LOST_BR,
+            "nocantrip" => ItemTags::NO_CANTRIP,
+            "monstermanual" => ItemTags::MONSTER_MANUAL,
+            "tearsup" => ItemTags::TEARS_UP,
+            "baby" => ItemTags::BABY,
+            "stars" => ItemTags::STARS,
+            "fly" => ItemTags::FLY,
+            "mom" => ItemTags::MOM,
+            "angel" => ItemTags::ANGEL,
+            "spider" => ItemTags::SPIDER,
+            "book" => ItemTags::BOOK,
+            "quest" => ItemTags::QUEST,
+            "nogreed" => ItemTags::NO_GREED,
+            "dead" => ItemTags::DEAD,
+            "uniquefamiliar" => ItemTags::UNIQUE_FAMILIAR,
+            "poop" => ItemTags::POOP,
+            "devil" => ItemTags::DEVIL,
+            "mushroom" => ItemTags::MUSHROOM,
+            "syringe" => ItemTags::SYRINGE,
+            "food" => ItemTags::FOOD,
+            "tech" => ItemTags::TECH,
+            "guppy" => ItemTags::GUPPY,
+            "battery" => ItemTags::BATTERY,
+            "bob" => ItemTags::BOB,
+            "nokeeper" => ItemTags::NO_KEEPER,
+            "nochallenge" => ItemTags::NO_CHALLENGE,
+            "nodaily" => ItemTags::NO_DAILY,
+            "noeden" => ItemTags::NO_EDEN,
+            _ => return None,
+        })
+    }
+
+    /// Parse a space separated tag list, ignoring tags we don't know about.
+    fn parse(tags: &str) -> ItemTags {
+        tags.split_whitespace()
+            .filter_map(ItemTags::from_name)
+            .fold(ItemTags::default(), |acc, tag| acc | tag)
+    }
+
+    pub fn contains(self, tags: ItemTags) -> bool {
+        self.0 & tags.0 == tags.0
+    }
+}
+
+impl BitOr for ItemTags {
+    type Output = ItemTags;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        ItemTags(self.0 | rhs.0)
+    }
+}
+
+trait Slotable: Clone + From<usize> + Into<usize> {}
+
+/// A key with a known number of values, so a map of it can be sized up front.
+trait FixedSlotable: Slotable {
     fn largest() -> Self;
 }
 
@@ -453,11 +762,34 @@ struct SlotMap<S: Slotable, T> {
 }
 
 impl<S: Slotable, T> SlotMap<S, T> {
-    fn into_iter(self) -> impl Iterator<Item = (S, T)> + DoubleEndedIterator {
-        self.data
-            .into_iter()
-            .enumerate()
-            .map(|(idx, t)| (S::from(idx), t))
+    fn with_largest(largest: S) -> Self
+    where
+        T: Default + Clone,
+    {
+        SlotMap {
+            data: vec![Default::default(); largest.into() + 1],
+            _p: PhantomData::default(),
+        }
+    }
+
+    fn largest(&self) -> S {
+        S::from(self.data.len() - 1)
+    }
+
+    fn get(&self, index: S) -> Option<&T> {
+        self.data.get(index.into())
+    }
+
+    /// Like indexing, but makes room for `index` if it's past the largest key.
+    fn get_or_grow(&mut self, index: S) -> &mut T
+    where
+        T: Default,
+    {
+        let index = index.into();
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, Default::default);
+        }
+        &mut self.data[index]
     }
 
     fn iter(&self) -> impl Iterator<Item = (S, &T)> + DoubleEndedIterator {
@@ -475,12 +807,9 @@ impl<S: Slotable, T> SlotMap<S, T> {
     }
 }
 
-impl<S: Slotable, T: Default + Clone> Default for SlotMap<S, T> {
+impl<S: FixedSlotable, T: Default + Clone> Default for SlotMap<S, T> {
     fn default() -> Self {
-        SlotMap {
-            data: vec![Default::default(); S::largest().into() + 1],
-            _p: PhantomData::default(),
-        }
+        SlotMap::with_largest(S::largest())
     }
 }
 
@@ -631,27 +960,145 @@ pub struct DeltaCrafter {
 #[wasm_bindgen]
 impl DeltaCrafter {
     #[wasm_bindgen(constructor)]
-    pub fn new(itempools_xml: &str, items_metadata_xml: &str) -> Result<DeltaCrafter, JsValue> {
-        Ok(DeltaCrafter {
-            crafter: BasicCrafter::new(itempools_xml, items_metadata_xml)?,
-            cache: Default::default(),
-            methods: Default::default(),
-            held: Default::default(),
-        })
+    pub fn new(
+        itempools_xml: &str,
+        items_metadata_xml: &str,
+    ) -> Result<DeltaCrafter, CrafterError> {
+        DeltaCrafter::with_seed(itempools_xml, items_metadata_xml, DEFAULT_SEED)
     }
 
-    pub fn pickups(&self) -> Result<HeldPickups, JsValue> {
-        let obj = js_sys::Object::new();
+    pub fn with_seed(
+        itempools_xml: &str,
+        items_metadata_xml: &str,
+        seed: u32,
+    ) -> Result<DeltaCrafter, CrafterError> {
+        Ok(DeltaCrafter::with_crafter(BasicCrafter::with_seed(
+            itempools_xml,
+            items_metadata_xml,
+            seed,
+        )?))
+    }
 
-        for (pickup, count) in self.held.iter() {
-            js_sys::Reflect::set(
-                &obj,
-                &JsValue::from(wasm_bindgen::convert::IntoWasmAbi::into_abi(pickup)),
-                &JsValue::from(*count),
-            )?;
+    /// A crafter for the base game files merged with those of any mods.
+    pub fn from_game_data(data: &GameData) -> DeltaCrafter {
+        DeltaCrafter::with_crafter(BasicCrafter::from_game_data(data))
+    }
+
+    /// A crafter using the bundled data and rules of `version`.
+    #[cfg(feature = "bundled-data")]
+    pub fn for_version(version: GameVersion) -> Result<DeltaCrafter, CrafterError> {
+        Ok(DeltaCrafter::with_crafter(BasicCrafter::for_version(
+            version,
+        )?))
+    }
+
+    pub fn with_unlock_profile(
+        itempools_xml: &str,
+        items_metadata_xml: &str,
+        unlocks: UnlockProfile,
+    ) -> Result<DeltaCrafter, CrafterError> {
+        let mut delta_crafter = DeltaCrafter::new(itempools_xml, items_metadata_xml)?;
+        delta_crafter.crafter.set_unlock_profile(Some(unlocks));
+        Ok(delta_crafter)
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.crafter.seed()
+    }
+
+    /// Change the seed crafting starts from, recomputing every recipe for the held pickups.
+    pub fn set_seed(&mut self, seed: u32) {
+        if seed == self.crafter.seed() {
+            return;
+        }
+        self.crafter.set_seed(seed);
+        self.rebuild();
+    }
+
+    /// `item` was rolled from `pool` this run, see [`PoolState::item_seen`].
+    pub fn item_seen(&mut self, item: ItemId, pool: ItemPool) -> Result<(), CrafterError> {
+        let item_id = js_item_id_to_item_id(item)?;
+        if self.crafter.pool_state_mut().item_seen(item_id, pool) {
+            self.rebuild();
         }
+        Ok(())
+    }
 
-        Ok(obj.unchecked_into())
+    /// `item` was picked up this run, see [`PoolState::item_taken`].
+    pub fn item_taken(&mut self, item: ItemId) -> Result<(), CrafterError> {
+        let item_id = js_item_id_to_item_id(item)?;
+        if self.crafter.pool_state_mut().item_taken(item_id) {
+            self.rebuild();
+        }
+        Ok(())
+    }
+
+    /// Only craft items unlocked in `unlocks`, or anything when `None`.
+    pub fn set_unlock_profile(&mut self, unlocks: Option<UnlockProfile>) {
+        if unlocks.as_ref() == self.crafter.unlock_profile() {
+            return;
+        }
+        self.crafter.set_unlock_profile(unlocks);
+        self.rebuild();
+    }
+
+    pub fn game_mode(&self) -> GameMode {
+        self.crafter.game_mode()
+    }
+
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        if game_mode == self.crafter.game_mode() {
+            return;
+        }
+        self.crafter.set_game_mode(game_mode);
+        self.rebuild();
+    }
+
+    pub fn character(&self) -> Character {
+        self.crafter.character()
+    }
+
+    pub fn set_character(&mut self, character: Character, birthright: bool) {
+        if character == self.crafter.character() && birthright == self.crafter.has_birthright() {
+            return;
+        }
+        self.crafter.set_character(character, birthright);
+        self.rebuild();
+    }
+
+    /// Craft with rules loaded from JSON, see [`CraftingRules`].
+    pub fn set_rules_json(&mut self, rules_json: &str) -> Result<(), CrafterError> {
+        self.set_rules(CraftingRules::from_json(rules_json)?);
+        Ok(())
+    }
+
+    pub fn add_builtin_modifier(&mut self, modifier: BuiltinModifier) {
+        self.add_modifier(Arc::from(modifier.modifier()));
+    }
+
+    pub fn clear_modifiers(&mut self) {
+        if self.crafter.modifiers().is_empty() {
+            return;
+        }
+        self.crafter.clear_modifiers();
+        self.rebuild();
+    }
+
+    /// Forget every item seen or taken, back to the pools of a fresh run.
+    pub fn reset_pools(&mut self) {
+        self.crafter.pool_state_mut().reset();
+        self.rebuild();
+    }
+
+    /// [`DeltaCrafter::set_seed`] with a seed as the game displays it, e.g. `"ABCD 1234"`.
+    pub fn set_seed_string(&mut self, seed: &str) -> Result<(), CrafterError> {
+        let seed = parse_seed(seed)?;
+        self.set_seed(seed);
+        Ok(())
+    }
+
+    pub fn pickups(&self) -> Result<HeldPickups, JsValue> {
+        held_pickups_to_js_held_pickups(&self.held)
     }
 
     pub fn items(&self) -> ItemIds {
@@ -672,7 +1119,7 @@ impl DeltaCrafter {
             if held_now == 8 {
                 let pickups = InternalPickups::new([pickup; 8]);
                 let item_id = self.craft(pickups);
-                assert!(self.methods[item_id].insert(pickups));
+                assert!(self.methods.get_or_grow(item_id).insert(pickups));
             }
             return;
         }
@@ -684,6 +1131,17 @@ impl DeltaCrafter {
         })
     }
 
+    pub fn add_entity_pickup(&mut self, variant: u32, subtype: u32) -> Result<(), CrafterError> {
+        let components = entity_pickup_components(variant, subtype)
+            .ok_or(CrafterError::NotABagPickup { variant, subtype })?;
+        for (pickup, count) in components.iter().copied() {
+            for _ in 0..count {
+                self.add_pickup(pickup);
+            }
+        }
+        Ok(())
+    }
+
     pub fn remove_pickup(&mut self, pickup: Pickup) {
         let held_before = self.held[pickup];
         if held_before == 0 {
@@ -714,10 +1172,70 @@ impl DeltaCrafter {
 
     pub fn get_recipes(&self, item: ItemId) -> Result<Recipes, JsValue> {
         let item_id = js_item_id_to_item_id(item)?;
-        let recipes: Vec<InternalPickups> = self.methods[item_id].iter().copied().collect();
+        let recipes: Vec<InternalPickups> = self
+            .methods
+            .get(item_id)
+            .map(|methods| methods.iter().copied().collect())
+            .unwrap_or_default();
         Ok(recipes_to_js_recipes(recipes.as_slice()).unchecked_into())
     }
 
+    /// The best set of at most `max_crafts` recipes that can be crafted one after the other with
+    /// the held pickups, valuing each item by `item_values` (items missing from it are worth
+    /// nothing). `complete` is false when the search gave up early and a better plan may exist.
+    pub fn plan_crafts(
+        &self,
+        item_values: ItemValues,
+        max_crafts: usize,
+    ) -> Result<JsCraftPlan, JsValue> {
+        let item_values: JsValue = item_values.unchecked_into();
+        let mut values = SlotMap::<InternalItemId, f64>::with_largest(self.methods.largest());
+        for (item_id, methods) in self.methods.iter() {
+            if !methods.is_empty() {
+                values[item_id] = js_sys::Reflect::get(&item_values, &JsValue::from(item_id.0))?
+                    .as_f64()
+                    .unwrap_or(0.);
+            }
+        }
+
+        let plan = self.best_crafts(|item_id| values[item_id], max_crafts);
+        let array = js_sys::Array::new_with_length(plan.crafts.len() as u32);
+        for (i, craft) in plan.crafts.iter().enumerate() {
+            let obj = js_sys::Object::new();
+            js_sys::Reflect::set(
+                &obj,
+                &JsValue::from("item"),
+                &item_id_to_js_item_id(craft.item),
+            )?;
+            js_sys::Reflect::set(
+                &obj,
+                &JsValue::from("recipe"),
+                &pickups_to_js_recipe(&craft.pickups),
+            )?;
+            array.set(i as u32, obj.unchecked_into());
+        }
+        let result = js_sys::Object::new();
+        js_sys::Reflect::set(&result, &JsValue::from("crafts"), &array)?;
+        js_sys::Reflect::set(
+            &result,
+            &JsValue::from("complete"),
+            &JsValue::from(plan.complete),
+        )?;
+        Ok(result.unchecked_into())
+    }
+
+    /// Throw away everything computed with the old crafter settings and add the held pickups back.
+    fn rebuild(&mut self) {
+        self.cache.clear();
+        let held = self.held.clone();
+        self.reset();
+        for (pickup, count) in held.iter() {
+            for _ in 0..*count {
+                self.add_pickup(pickup);
+            }
+        }
+    }
+
     fn craft(&mut self, pickups: InternalPickups) -> InternalItemId {
         if let Some(item_id) = self.cache.get(&pickups).copied() {
             item_id
@@ -743,195 +1261,414 @@ impl DeltaCrafter {
             InternalPickups::new(pickups)
         }) {
             let item_id = self.craft(pickups);
-            f(&mut self.methods[item_id], pickups);
+            f(self.methods.get_or_grow(item_id), pickups);
         }
     }
 }
 
+impl DeltaCrafter {
+    pub fn with_crafter(crafter: BasicCrafter) -> Self {
+        DeltaCrafter {
+            methods: SlotMap::with_largest(crafter.largest_item()),
+            crafter,
+            cache: Default::default(),
+            held: Default::default(),
+        }
+    }
+
+    /// Rust side of [`DeltaCrafter::plan_crafts`], valuing items with an arbitrary function, e.g.
+    /// [`BasicCrafter::item_quality`] or membership in a wishlist.
+    pub fn best_crafts<F: Fn(InternalItemId) -> f64>(
+        &self,
+        value: F,
+        max_crafts: usize,
+    ) -> CraftPlan {
+        planner::plan_crafts(&self.methods, &self.held, value, max_crafts)
+    }
+
+    pub fn crafter(&self) -> &BasicCrafter {
+        &self.crafter
+    }
+
+    pub fn set_pool_state(&mut self, pools: PoolState) {
+        self.crafter.set_pool_state(pools);
+        self.rebuild();
+    }
+
+    pub fn add_modifier(&mut self, modifier: Arc<dyn RunModifier>) {
+        self.crafter.add_modifier(modifier);
+        self.rebuild();
+    }
+
+    pub fn set_rules(&mut self, rules: CraftingRules) {
+        if &rules == self.crafter.rules() {
+            return;
+        }
+        self.crafter.set_rules(rules);
+        self.rebuild();
+    }
+}
+
 trait Crafter {
     fn craft(&self, pickups: InternalPickups) -> InternalItemId;
 }
 
 #[derive(Debug, Clone)]
 pub struct BasicCrafter {
-    pool_item_weights: HashMap<ItemPool, HashMap<InternalItemId, f32>>,
+    pools: PoolState,
+    largest_item: InternalItemId,
     item_qualities: SlotMap<InternalItemId, u32>,
+    item_tags: SlotMap<InternalItemId, ItemTags>,
+    seed: u32,
+    unlocks: Option<UnlockProfile>,
+    game_mode: GameMode,
+    character: Character,
+    birthright: bool,
+    modifiers: Vec<Arc<dyn RunModifier>>,
+    rules: CraftingRules,
+    /// Built on the first craft, and again after anything that changes which items a roll picks.
+    roll_tables: OnceLock<RollTables>,
 }
 
 impl BasicCrafter {
-    fn new(itempools_xml: &str, items_metadata_xml: &str) -> Result<Self, JsValue> {
+    pub fn new(itempools_xml: &str, items_metadata_xml: &str) -> Result<Self, CrafterError> {
+        BasicCrafter::with_seed(itempools_xml, items_metadata_xml, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(
+        itempools_xml: &str,
+        items_metadata_xml: &str,
+        seed: u32,
+    ) -> Result<Self, CrafterError> {
+        let mut crafter =
+            BasicCrafter::from_game_data(&GameData::new(itempools_xml, items_metadata_xml)?);
+        crafter.set_seed(seed);
+        Ok(crafter)
+    }
+
+    /// A crafter for the base game files merged with those of any mods.
+    pub fn from_game_data(data: &GameData) -> Self {
         set_panic_hook();
-        Ok(BasicCrafter {
-            pool_item_weights: get_pool_item_weights(itempools_xml)?,
-            item_qualities: get_item_qualities(items_metadata_xml)?,
-        })
+        let largest_item = data.largest_item_id();
+        let mut item_qualities = SlotMap::with_largest(largest_item);
+        let mut item_tags = SlotMap::with_largest(largest_item);
+        for item in data.metadata.values() {
+            item_qualities[item.id] = item.quality;
+            item_tags[item.id] = ItemTags::parse(&item.tags);
+        }
+        BasicCrafter {
+            pools: PoolState::from_pools(data.pools.clone()),
+            largest_item,
+            item_qualities,
+            item_tags,
+            seed: DEFAULT_SEED,
+            unlocks: None,
+            game_mode: GameMode::default(),
+            character: Character::default(),
+            birthright: false,
+            modifiers: Vec::new(),
+            rules: CraftingRules::default(),
+            roll_tables: OnceLock::new(),
+        }
+    }
+
+    /// The largest item id in the pools or the item metadata.
+    pub fn largest_item(&self) -> InternalItemId {
+        self.largest_item
+    }
+
+    /// A crafter using the bundled data and rules of `version`.
+    #[cfg(feature = "bundled-data")]
+    pub fn for_version(version: GameVersion) -> Result<Self, CrafterError> {
+        let mut crafter =
+            BasicCrafter::new(&version.itempools_xml()?, &version.items_metadata_xml()?)?;
+        crafter.set_rules(version.rules());
+        Ok(crafter)
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    pub fn unlock_profile(&self) -> Option<&UnlockProfile> {
+        self.unlocks.as_ref()
+    }
+
+    pub fn set_unlock_profile(&mut self, unlocks: Option<UnlockProfile>) {
+        self.unlocks = unlocks;
+        self.roll_tables = OnceLock::new();
+    }
+
+    pub fn game_mode(&self) -> GameMode {
+        self.game_mode
+    }
+
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        self.game_mode = game_mode;
+        self.roll_tables = OnceLock::new();
+    }
+
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    pub fn has_birthright(&self) -> bool {
+        self.birthright
+    }
+
+    /// Play as `character`, holding Birthright or not.
+    pub fn set_character(&mut self, character: Character, birthright: bool) {
+        self.character = character;
+        self.birthright = birthright;
+        self.roll_tables = OnceLock::new();
+    }
+
+    pub fn rules(&self) -> &CraftingRules {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: CraftingRules) {
+        self.rules = rules;
+        self.roll_tables = OnceLock::new();
+    }
+
+    pub fn modifiers(&self) -> &[Arc<dyn RunModifier>] {
+        self.modifiers.as_slice()
+    }
+
+    pub fn add_modifier(&mut self, modifier: Arc<dyn RunModifier>) {
+        self.modifiers.push(modifier);
+    }
+
+    pub fn clear_modifiers(&mut self) {
+        self.modifiers.clear();
+    }
+
+    pub fn item_tags(&self, item: InternalItemId) -> ItemTags {
+        self.item_tags.get(item).copied().unwrap_or_default()
+    }
+
+    /// Whether `item` can't come out of a roll with the current settings, whatever pool it's in.
+    fn is_excluded(&self, item: InternalItemId) -> bool {
+        let tags = self.item_tags(item);
+        self.unlocks
+            .as_ref()
+            .is_some_and(|unlocks| unlocks.is_item_locked(item))
+            || (self.game_mode.is_greed() && tags.contains(ItemTags::NO_GREED))
+            || tags.contains(ItemTags::QUEST)
+            || !self.character.allows(tags, self.birthright)
+    }
+
+    pub fn pool_state(&self) -> &PoolState {
+        &self.pools
+    }
+
+    pub fn pool_state_mut(&mut self) -> &mut PoolState {
+        self.roll_tables = OnceLock::new();
+        &mut self.pools
+    }
+
+    pub fn set_pool_state(&mut self, pools: PoolState) {
+        self.largest_item = self.largest_item.max(pools.largest_item());
+        self.pools = pools;
+        self.roll_tables = OnceLock::new();
+    }
+
+    pub fn item_quality(&self, item: InternalItemId) -> u32 {
+        self.item_qualities.get(item).copied().unwrap_or(0)
     }
 }
 
 impl Crafter for BasicCrafter {
     fn craft(&self, pickups: InternalPickups) -> InternalItemId {
-        let mut rng = Rng::default();
-        let pickup_counts = pickups.pickups.iter().fold(BTreeMap::new(), |mut acc, p| {
+        let tables = self.roll_tables.get_or_init(|| RollTables::new(self));
+        self.craft_with(pickups, Some(tables))
+    }
+}
+
+impl BasicCrafter {
+    /// Find the rolled item with `tables` when they're precise enough to, by walking every item's
+    /// weight otherwise.
+    fn craft_with(&self, pickups: InternalPickups, tables: Option<&RollTables>) -> InternalItemId {
+        let mut rng = Rng::new(self.seed);
+        let pickups = pickups.pickups();
+        let pickup_counts = pickups.iter().fold(BTreeMap::new(), |mut acc, p| {
             *acc.entry(*p).or_default() += 1;
             acc
         });
-        let pickup_weight_total: u32 = pickups.pickups.iter().map(|pickup| pickup.weight()).sum();
-        for pickup in pickups.pickups.iter() {
+        let pickup_weight_total: u32 = pickups.iter().map(|pickup| pickup.weight()).sum();
+        for pickup in pickups.iter() {
             rng.shifts = pickup.shifts();
             rng.next();
         }
 
-        fn get_pickup_count(pickup_counts: &BTreeMap<Pickup, u32>, pickup: Pickup) -> u32 {
-            pickup_counts.get(&pickup).copied().unwrap_or(0)
-        }
-
         rng.shifts = (1, 21, 20);
-        let mut pool_weights: Vec<(ItemPool, f32)> = vec![
-            (ItemPool::Treasure, 1.),
-            (ItemPool::Shop, 2.),
-            (ItemPool::Boss, 2.),
-            (
-                ItemPool::Angel,
-                get_pickup_count(&pickup_counts, Pickup::EternalHeart) as f32 * 10.,
-            ),
-            (
-                ItemPool::Devil,
-                get_pickup_count(&pickup_counts, Pickup::BlackHeart) as f32 * 10.,
-            ),
-            (
-                ItemPool::Secret,
-                get_pickup_count(&pickup_counts, Pickup::BoneHeart) as f32 * 5.,
-            ),
-            (
-                ItemPool::GoldenChest,
-                get_pickup_count(&pickup_counts, Pickup::GoldHeart) as f32 * 10.,
-            ),
-            (
-                ItemPool::Curse,
-                get_pickup_count(&pickup_counts, Pickup::RottenHeart) as f32 * 10.,
-            ),
-            (
-                ItemPool::RedChest,
-                get_pickup_count(&pickup_counts, Pickup::CrackedKey) as f32 * 10.,
-            ),
-        ];
+        let mut pool_weights = self
+            .rules
+            .pool_weights(|pickup| pickup_counts.get(&pickup).copied().unwrap_or(0));
 
-        let mut combined = 0;
-        for i in [Pickup::RedHeart, Pickup::Penny, Pickup::Key, Pickup::Bomb].iter() {
-            combined += get_pickup_count(&pickup_counts, *i);
-        }
-        if combined == 0 {
-            pool_weights.push((
-                ItemPool::Planetarium,
-                get_pickup_count(&pickup_counts, Pickup::Rune) as f32 * 10.,
-            ));
+        for modifier in self.modifiers.iter() {
+            modifier.pool_weights(&mut pool_weights);
         }
 
-        let mut item_weights = SlotMap::<InternalItemId, f32>::default();
-        let mut weight_total = 0_f32;
-
+        let mut pools = Vec::with_capacity(pool_weights.len());
         for (pool, pool_weight) in pool_weights.iter() {
             if *pool_weight <= 0. {
                 continue;
             }
 
-            let mut quality_check_val = pickup_weight_total;
-            match pool {
-                ItemPool::Devil | ItemPool::Angel | ItemPool::Secret => quality_check_val -= 5,
-                _ => (),
+            let mut quality_bounds = self.rules.quality_bounds(*pool, pickup_weight_total);
+            for modifier in self.modifiers.iter() {
+                quality_bounds = modifier.quality_bounds(*pool, quality_bounds);
             }
+            pools.push((self.game_mode.pool(*pool), *pool_weight, quality_bounds));
+        }
 
-            let mut quality_bounds = 0..=0;
-            for (min, bounds) in QUALITY_BOUNDS_LIST.iter() {
-                if quality_check_val > *min {
-                    quality_bounds = bounds.clone();
-                    break;
-                }
+        let weights = tables.and_then(|tables| tables.weights(&pools));
+        let mut item_weights = None;
+        let mut roll = || {
+            let roll = rng.next_float();
+            if let Some(item) = weights.as_ref().and_then(|weights| weights.roll(roll)) {
+                return item.unwrap_or(self.rules.fallback_item);
             }
-            let items_with_weights = self.pool_item_weights.get(pool).unwrap();
-            for (item, weight) in items_with_weights.iter() {
-                if quality_bounds.contains(&self.item_qualities[*item]) {
-                    let final_weight = *pool_weight * weight;
-                    item_weights[*item] += final_weight;
-                    weight_total += final_weight;
+
+            let (item_weights, weight_total) =
+                item_weights.get_or_insert_with(|| self.item_weights(&pools));
+            let mut target = roll * *weight_total;
+            for (item, weight) in item_weights.iter() {
+                target -= weight;
+                if target < 0. {
+                    return item;
                 }
             }
+
+            self.rules.fallback_item
+        };
+
+        let mut item = roll();
+        for modifier in self.modifiers.iter() {
+            item = modifier.roll(
+                item,
+                &mut RollContext {
+                    quality: &|item| self.item_quality(item),
+                    reroll: &mut roll,
+                },
+            );
         }
+        item
+    }
+
+    /// Every item's weight when rolling from `pools`, and their total, added up in the same order
+    /// as the game.
+    fn item_weights(
+        &self,
+        pools: &[(ItemPool, f32, RangeInclusive<u32>)],
+    ) -> (SlotMap<InternalItemId, f32>, f32) {
+        let mut item_weights = SlotMap::<InternalItemId, f32>::with_largest(self.largest_item);
+        let mut weight_total = 0_f32;
 
-        let mut target = rng.next_float() * weight_total;
-        for (item, weight) in item_weights.into_iter() {
-            target -= weight;
-            if target < 0. {
-                return item;
+        for (pool, pool_weight, quality_bounds) in pools.iter() {
+            for (item, entry) in self.pools.items(*pool) {
+                if quality_bounds.contains(&self.item_quality(*item)) && !self.is_excluded(*item) {
+                    let final_weight = *pool_weight * entry.weight;
+                    item_weights[*item] += final_weight;
+                    weight_total += final_weight;
+                }
             }
         }
-
-        InternalItemId::from(25_u16)
+        (item_weights, weight_total)
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+/// Eight pickups, sorted and packed 5 bits each with the first in the highest bits, so the packed
+/// value orders like the sorted array does.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct InternalPickups {
-    pickups: [Pickup; 8],
+    packed: u64,
 }
 
 impl InternalPickups {
-    fn new(pickups: [Pickup; 8]) -> Self {
+    const BITS: u32 = 5;
+    const MASK: u64 = (1 << InternalPickups::BITS) - 1;
+
+    fn new(mut pickups: [Pickup; 8]) -> Self {
+        pickups.sort_unstable();
         InternalPickups {
-            pickups: sort_pickups(pickups),
+            packed: pickups.iter().fold(0, |packed, pickup| {
+                packed << InternalPickups::BITS | usize::from(*pickup) as u64
+            }),
         }
     }
-}
 
-fn sort_pickups(mut pickups: [Pickup; 8]) -> [Pickup; 8] {
-    for i in 0..pickups.len() {
-        for j in i..pickups.len() {
-            if pickups[j] < pickups[i] {
-                pickups.swap(i, j);
-            }
+    /// Sorted.
+    fn pickups(self) -> [Pickup; 8] {
+        let mut pickups = [Pickup::RedHeart; 8];
+        for (i, pickup) in pickups.iter_mut().rev().enumerate() {
+            let slot = self.packed >> (i as u32 * InternalPickups::BITS) & InternalPickups::MASK;
+            *pickup = Pickup::from(slot as usize);
         }
+        pickups
+    }
+}
+
+impl Debug for InternalPickups {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("InternalPickups")
+            .field(&self.pickups())
+            .finish()
+    }
+}
+
+fn held_pickups_to_js_held_pickups(held: &SlotMap<Pickup, u8>) -> Result<HeldPickups, JsValue> {
+    let obj = js_sys::Object::new();
+
+    for (pickup, count) in held.iter() {
+        js_sys::Reflect::set(
+            &obj,
+            &JsValue::from(wasm_bindgen::convert::IntoWasmAbi::into_abi(pickup)),
+            &JsValue::from(*count),
+        )?;
+    }
+
+    Ok(obj.unchecked_into())
+}
+
+fn pickups_to_js_recipe(pickups: &[Pickup]) -> Recipe {
+    let r = js_sys::Array::new_with_length(pickups.len() as u32);
+    for (j, p) in pickups.iter().copied().enumerate() {
+        r.set(
+            j as u32,
+            JsValue::from(wasm_bindgen::convert::IntoWasmAbi::into_abi(p)),
+        );
     }
-    pickups
+    r.unchecked_into()
 }
 
 fn recipes_to_js_recipes(recipes: &[InternalPickups]) -> JsValue {
     let array = js_sys::Array::new_with_length(recipes.len() as u32);
-    for (i, recipe) in recipes.iter().copied().enumerate() {
-        let r = js_sys::Array::new_with_length(8);
-        for (j, p) in recipe.pickups.iter().copied().enumerate() {
-            r.set(
-                j as u32,
-                JsValue::from(wasm_bindgen::convert::IntoWasmAbi::into_abi(p)),
-            );
-        }
-        array.set(i as u32, r.unchecked_into());
+    for (i, recipe) in recipes.iter().enumerate() {
+        array.set(
+            i as u32,
+            pickups_to_js_recipe(&recipe.pickups()).unchecked_into(),
+        );
     }
     array.unchecked_into()
 }
 
-fn js_item_id_to_item_id(item_id: ItemId) -> Result<InternalItemId, JsValue> {
+fn js_item_id_to_item_id(item_id: ItemId) -> Result<InternalItemId, CrafterError> {
     let js_value: JsValue = item_id.unchecked_into();
-    let id: f64 = js_value.as_f64().map(Ok).unwrap_or_else(|| {
-        Err(JsValue::from(format!(
-            "{:?} is not a valid item id",
-            js_value
-        )))
-    })?;
+    let id: f64 = js_value
+        .as_f64()
+        .ok_or_else(|| CrafterError::InvalidItemId(format!("{:?}", js_value)))?;
     let r = id.round() as u16;
     if (r as f64 - id).abs() > 0.1 {
-        return Err(JsValue::from(format!(
-            "{:?} is not a valid item id",
-            js_value
-        )));
-    }
-    let f = InternalItemId(r);
-    if f > InternalItemId::largest() {
-        return Err(JsValue::from(format!(
-            "{:?} is not a valid item id",
-            js_value
-        )));
+        return Err(CrafterError::InvalidItemId(format!("{:?}", js_value)));
     }
-    Ok(f)
+    Ok(InternalItemId(r))
 }
 
 fn item_id_to_js_item_id(item_id: InternalItemId) -> ItemId {
@@ -1013,10 +1750,405 @@ mod tests {
         )
     }
 
+    #[test]
+    fn craft_golden_pickups() {
+        // weight 7 each, with the same pools, only their shifts tell them apart
+        for (pickup, item) in [(GoldenPenny, 232), (GoldenPill, 234), (GoldenBattery, 245)].iter() {
+            let crafted = SIMPLE_CACHE.craft(InternalPickups::new([*pickup; 8]));
+            assert_eq!(InternalItemId(*item), crafted);
+            assert_eq!(4, SIMPLE_CACHE.item_quality(crafted));
+        }
+        assert_eq!(
+            InternalItemId(169),
+            SIMPLE_CACHE.craft(InternalPickups::new([
+                GoldenPenny,
+                GoldenPenny,
+                GoldenPill,
+                GoldenPill,
+                GoldenBattery,
+                GoldenBattery,
+                PoopNugget,
+                PoopNugget,
+            ]))
+        );
+
+        // poop nuggets weigh nothing, so only quality 0 and 1 items can be made
+        let crafted = SIMPLE_CACHE.craft(InternalPickups::new([PoopNugget; 8]));
+        assert_eq!(InternalItemId(177), crafted);
+        assert_eq!(0, SIMPLE_CACHE.item_quality(crafted));
+    }
+
+    #[test]
+    fn craft_with_seed() {
+        let pickups = InternalPickups::new([
+            SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart,
+        ]);
+        let mut crafter = SIMPLE_CACHE.clone();
+        crafter.set_seed(DEFAULT_SEED);
+        assert_eq!(SIMPLE_CACHE.craft(pickups), crafter.craft(pickups));
+        crafter.set_seed(0x12345678);
+        assert_ne!(SIMPLE_CACHE.craft(pickups), crafter.craft(pickups));
+    }
+
+    #[test]
+    fn delta_crafter_set_seed() {
+        let mut delta_crafter = get_delta_crafter();
+        for p in [
+            RedHeart, RedHeart, SoulHeart, Penny, Penny, Nickel, LuckyPenny, Key, LilBattery,
+        ]
+        .iter()
+        {
+            delta_crafter.add_pickup(*p);
+        }
+        let unseeded = delta_crafter.methods.clone();
+
+        delta_crafter.set_seed(0x12345678);
+        let mut expected = SlotMap::<InternalItemId, HashSet<InternalPickups>>::with_largest(
+            delta_crafter.methods.largest(),
+        );
+        for recipes in unseeded.iter().map(|(_, r)| r) {
+            for pickups in recipes.iter().copied() {
+                expected[delta_crafter.crafter.craft(pickups)].insert(pickups);
+            }
+        }
+        assert_eq!(delta_crafter.methods, expected);
+        assert_ne!(delta_crafter.methods, unseeded);
+
+        delta_crafter.set_seed(DEFAULT_SEED);
+        assert_eq!(delta_crafter.methods, unseeded);
+    }
+
+    #[test]
+    fn craft_with_pool_state() {
+        let pickups = InternalPickups::new([
+            SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart,
+        ]);
+        let mut crafter = SIMPLE_CACHE.clone();
+        assert_eq!(InternalItemId(114), crafter.craft(pickups));
+        assert!(crafter.pool_state_mut().item_taken(InternalItemId(114)));
+        assert_ne!(InternalItemId(114), crafter.craft(pickups));
+    }
+
+    #[test]
+    fn delta_crafter_item_taken() {
+        let mut delta_crafter = get_delta_crafter();
+        for _ in 0..8 {
+            delta_crafter.add_pickup(SoulHeart);
+        }
+        assert!(!delta_crafter.methods[InternalItemId(114)].is_empty());
+
+        let mut pools = delta_crafter.crafter.pool_state().clone();
+        pools.item_taken(InternalItemId(114));
+        delta_crafter.set_pool_state(pools);
+        assert!(delta_crafter.methods[InternalItemId(114)].is_empty());
+        assert_eq!(
+            1,
+            delta_crafter
+                .methods
+                .iter()
+                .filter(|(_, m)| !m.is_empty())
+                .count()
+        );
+    }
+
+    #[test]
+    fn craft_with_unlock_profile() {
+        let pickups = InternalPickups::new([
+            SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart, SoulHeart,
+        ]);
+        let mut crafter = SIMPLE_CACHE.clone();
+        crafter.set_unlock_profile(Some(UnlockProfile::new()));
+        assert_eq!(InternalItemId(114), crafter.craft(pickups));
+        crafter.set_unlock_profile(Some(std::iter::once(InternalItemId(114)).collect()));
+        assert_ne!(InternalItemId(114), crafter.craft(pickups));
+    }
+
+    #[test]
+    fn delta_crafter_unlock_profile() {
+        let mut delta_crafter = DeltaCrafter::with_unlock_profile(
+            ITEMPOOLS_XML,
+            ITEMS_METADATA_XML,
+            std::iter::once(InternalItemId(114)).collect(),
+        )
+        .unwrap();
+        for _ in 0..8 {
+            delta_crafter.add_pickup(SoulHeart);
+        }
+        assert!(delta_crafter.methods[InternalItemId(114)].is_empty());
+
+        delta_crafter.set_unlock_profile(None);
+        assert!(!delta_crafter.methods[InternalItemId(114)].is_empty());
+    }
+
+    #[test]
+    fn craft_greed() {
+        let mut crafter = SIMPLE_CACHE.clone();
+        crafter.set_game_mode(GameMode::Greedier);
+        let greed_pools = [
+            ItemPool::GreedTreasure,
+            ItemPool::GreedShop,
+            ItemPool::GreedBoss,
+            ItemPool::GreedAngel,
+            ItemPool::GreedDevil,
+            ItemPool::GreedSecret,
+            ItemPool::GreedCurse,
+            ItemPool::GoldenChest,
+            ItemPool::RedChest,
+            ItemPool::Planetarium,
+        ];
+        for pickups in PickupIterator::new(
+            {
+                let mut held = SlotMap::<Pickup, u8>::default();
+                for p in [
+                    SoulHeart,
+                    BlackHeart,
+                    EternalHeart,
+                    Rune,
+                    LuckyPenny,
+                    GoldHeart,
+                ]
+                .iter()
+                {
+                    held[*p] = 3;
+                }
+                held
+            },
+            8,
+        )
+        .take(500)
+        {
+            let item = crafter.craft(InternalPickups::new(pickups));
+            assert!(!crafter.item_tags(item).contains(ItemTags::NO_GREED));
+            assert!(
+                greed_pools
+                    .iter()
+                    .any(|pool| crafter.pool_state().weight(*pool, item).is_some()),
+                "{:?} isn't in a greed pool",
+                item
+            );
+        }
+    }
+
+    #[test]
+    fn delta_crafter_game_mode() {
+        let mut delta_crafter = get_delta_crafter();
+        for _ in 0..8 {
+            delta_crafter.add_pickup(SoulHeart);
+        }
+        let normal = delta_crafter.methods.clone();
+        delta_crafter.set_game_mode(GameMode::Greed);
+        let mut greed_crafter = SIMPLE_CACHE.clone();
+        greed_crafter.set_game_mode(GameMode::Greed);
+        let greed_item = greed_crafter.craft(InternalPickups::new([SoulHeart; 8]));
+        assert!(!delta_crafter.methods[greed_item].is_empty());
+        delta_crafter.set_game_mode(GameMode::Normal);
+        assert_eq!(normal, delta_crafter.methods);
+    }
+
+    #[test]
+    fn internal_pickups_packing() {
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in [RedHeart, Penny, Rune, GoldenBattery, PoopNugget].iter() {
+            held[*p] = 8;
+        }
+        let recipes: Vec<[Pickup; 8]> = PickupIterator::new(held, 8).map(sort_array).collect();
+        for (a, b) in recipes.iter().tuple_combinations() {
+            let (packed_a, packed_b) = (InternalPickups::new(*a), InternalPickups::new(*b));
+            assert_eq!(*a, packed_a.pickups());
+            assert_eq!(a.cmp(b), packed_a.cmp(&packed_b));
+            assert_eq!(a == b, packed_a == packed_b);
+        }
+
+        let mut unsorted = [
+            PoopNugget, RedHeart, Rune, Penny, PoopNugget, Key, Bomb, Card,
+        ];
+        let pickups = InternalPickups::new(unsorted);
+        unsorted.sort();
+        assert_eq!(unsorted, pickups.pickups());
+        assert_eq!(
+            format!("InternalPickups({:?})", unsorted),
+            format!("{:?}", pickups)
+        );
+    }
+
+    fn sort_array(mut pickups: [Pickup; 8]) -> [Pickup; 8] {
+        pickups.sort();
+        pickups
+    }
+
+    #[test]
+    fn craft_with_missing_pools() {
+        let crafter = BasicCrafter::new(
+            r#"<ItemPools><Pool Name="treasure"><Item Id="1" Weight="1"/></Pool></ItemPools>"#,
+            r#"<items><item id="1" quality="1"/></items>"#,
+        )
+        .unwrap();
+        // eternal hearts weight the angel pool, which isn't there
+        assert_eq!(
+            crafter.rules().fallback_item,
+            crafter.craft(InternalPickups::new([EternalHeart; 8]))
+        );
+        assert_eq!(
+            InternalItemId(1),
+            crafter.craft(InternalPickups::new([Penny; 8]))
+        );
+    }
+
+    #[test]
+    fn item_tags() {
+        assert_eq!(
+            ItemTags::SUMMONABLE | ItemTags::TEARS_UP | ItemTags::OFFENSIVE,
+            SIMPLE_CACHE.item_tags(InternalItemId(1))
+        );
+        assert!(SIMPLE_CACHE
+            .item_tags(InternalItemId(1))
+            .contains(ItemTags::OFFENSIVE | ItemTags::SUMMONABLE));
+        assert!(!SIMPLE_CACHE
+            .item_tags(InternalItemId(1))
+            .contains(ItemTags::QUEST));
+    }
+
+    #[test]
+    fn craft_tainted_lost() {
+        let mut crafter = SIMPLE_CACHE.clone();
+        crafter.set_character(Character::TaintedLost, false);
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in [SoulHeart, RedHeart, Penny, Key, Bomb, Card].iter() {
+            held[*p] = 3;
+        }
+        for pickups in PickupIterator::new(held, 8).take(500) {
+            let item = crafter.craft(InternalPickups::new(pickups));
+            assert!(crafter.item_tags(item).contains(ItemTags::OFFENSIVE));
+        }
+    }
+
+    #[test]
+    fn craft_sacred_orb() {
+        let mut crafter = SIMPLE_CACHE.clone();
+        crafter.add_modifier(Arc::new(SacredOrb));
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in [RedHeart, Penny, Key, Bomb, Card, Pill].iter() {
+            held[*p] = 3;
+        }
+        let mut rerolled = 0;
+        for pickups in PickupIterator::new(held, 8) {
+            let pickups = InternalPickups::new(pickups);
+            let before = SIMPLE_CACHE.craft(pickups);
+            let after = crafter.craft(pickups);
+            if SIMPLE_CACHE.item_quality(before) >= 2 {
+                assert_eq!(before, after);
+            } else if before != after {
+                rerolled += 1;
+            }
+        }
+        assert_ne!(0, rerolled);
+    }
+
+    #[test]
+    fn roll_tables_match_item_weights() {
+        let mut crafters = vec![SIMPLE_CACHE.clone()];
+        let mut greed = SIMPLE_CACHE.clone();
+        greed.set_game_mode(GameMode::Greedier);
+        crafters.push(greed);
+        let mut chaos = SIMPLE_CACHE.clone();
+        chaos.add_modifier(Arc::new(Chaos));
+        chaos.add_modifier(Arc::new(SacredOrb));
+        crafters.push(chaos);
+        let mut locked = SIMPLE_CACHE.clone();
+        locked.set_unlock_profile(Some((1..300).map(InternalItemId).collect()));
+        locked.set_character(Character::TaintedLost, false);
+        crafters.push(locked);
+        let mut seen = SIMPLE_CACHE.clone();
+        seen.set_seed(12345);
+        // builds the tables before the pools change under them
+        seen.craft(InternalPickups::new([SoulHeart; 8]));
+        for item in [114, 363, 182].iter() {
+            seen.pool_state_mut().item_taken(InternalItemId(*item));
+        }
+        crafters.push(seen);
+
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in [RedHeart, SoulHeart, BlackHeart, Penny, Key, Card, Rune].iter() {
+            held[*p] = 8;
+        }
+        for crafter in crafters.iter() {
+            for pickups in PickupIterator::new(held.clone(), 8).step_by(7) {
+                let pickups = InternalPickups::new(pickups);
+                assert_eq!(
+                    crafter.craft_with(pickups, None),
+                    crafter.craft(pickups),
+                    "{:?}",
+                    pickups
+                );
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct OnlyBoss;
+
+    impl RunModifier for OnlyBoss {
+        fn pool_weights(&self, pool_weights: &mut Vec<(ItemPool, f32)>) {
+            pool_weights.retain(|(pool, _)| *pool == ItemPool::Boss);
+        }
+    }
+
+    #[test]
+    fn delta_crafter_modifier() {
+        let mut delta_crafter = get_delta_crafter();
+        for _ in 0..8 {
+            delta_crafter.add_pickup(SoulHeart);
+        }
+        let unmodified = delta_crafter.methods.clone();
+
+        delta_crafter.add_modifier(Arc::new(OnlyBoss));
+        let (item, _) = delta_crafter
+            .methods
+            .iter()
+            .find(|(_, m)| !m.is_empty())
+            .unwrap();
+        assert!(delta_crafter
+            .crafter
+            .pool_state()
+            .weight(ItemPool::Boss, item)
+            .is_some());
+
+        delta_crafter.clear_modifiers();
+        assert_eq!(unmodified, delta_crafter.methods);
+    }
+
+    #[test]
+    fn delta_crafter_rules() {
+        let mut delta_crafter = get_delta_crafter();
+        for _ in 0..8 {
+            delta_crafter.add_pickup(SoulHeart);
+        }
+        let unmodified = delta_crafter.methods.clone();
+
+        delta_crafter.set_rules(CraftingRules::default());
+        assert_eq!(unmodified, delta_crafter.methods);
+
+        let mut rules = CraftingRules::default();
+        rules.pools.retain(|rule| rule.pool == ItemPool::Boss);
+        delta_crafter.set_rules(rules);
+        let (item, _) = delta_crafter
+            .methods
+            .iter()
+            .find(|(_, m)| !m.is_empty())
+            .unwrap();
+        assert!(delta_crafter
+            .crafter
+            .pool_state()
+            .weight(ItemPool::Boss, item)
+            .is_some());
+    }
+
     #[test]
     fn delta_crafter() {
         let mut delta_crafter = get_delta_crafter();
-        let mut methods = SlotMap::<InternalItemId, HashSet<InternalPickups>>::default();
+        let mut methods = SlotMap::<InternalItemId, HashSet<InternalPickups>>::with_largest(
+            delta_crafter.methods.largest(),
+        );
         let mut held = SlotMap::<Pickup, u8>::default();
         assert_eq!(delta_crafter.held, held);
         assert_eq!(delta_crafter.methods, methods);
@@ -1179,7 +2311,9 @@ mod tests {
     #[test]
     fn delta_crafter_at_8() {
         let mut delta_crafter = get_delta_crafter();
-        let mut methods = SlotMap::<InternalItemId, HashSet<InternalPickups>>::default();
+        let mut methods = SlotMap::<InternalItemId, HashSet<InternalPickups>>::with_largest(
+            delta_crafter.methods.largest(),
+        );
         let mut held = SlotMap::<Pickup, u8>::default();
         assert_eq!(delta_crafter.held, held);
         assert_eq!(delta_crafter.methods, methods);
@@ -1262,6 +2396,30 @@ mod tests {
         assert_eq!(delta_crafter.methods, methods);
     }
 
+    #[test]
+    fn delta_crafter_entity_pickup() {
+        let mut delta_crafter = get_delta_crafter();
+        let mut methods = SlotMap::<InternalItemId, HashSet<InternalPickups>>::with_largest(
+            delta_crafter.methods.largest(),
+        );
+        let mut held = SlotMap::<Pickup, u8>::default();
+
+        for _ in 0..3 {
+            delta_crafter.add_entity_pickup(10, 5).unwrap();
+            held[RedHeart] += 2;
+            assert_eq!(delta_crafter.held, held);
+            assert_eq!(delta_crafter.methods, methods);
+        }
+
+        delta_crafter.add_entity_pickup(10, 5).unwrap();
+        held[RedHeart] += 2;
+        methods[InternalItemId(599)].insert(InternalPickups::new([
+            RedHeart, RedHeart, RedHeart, RedHeart, RedHeart, RedHeart, RedHeart, RedHeart,
+        ]));
+        assert_eq!(delta_crafter.held, held);
+        assert_eq!(delta_crafter.methods, methods);
+    }
+
     #[test]
     fn pickup_iterator() {
         let mut pickups = vec![
@@ -1344,4 +2502,48 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn pickup_index_round_trip() {
+        for pickup in Pickup::iter() {
+            assert_eq!(pickup, Pickup::from(usize::from(pickup)));
+        }
+        assert_eq!(Pickup::iter().count(), usize::from(Pickup::largest()) + 1);
+    }
+
+    #[test]
+    fn pickup_iterator_golden() {
+        let mut pickups = [
+            GoldenPenny,
+            GoldenPenny,
+            GoldenPill,
+            GoldenBattery,
+            PoopNugget,
+            PoopNugget,
+            CrackedKey,
+            RedHeart,
+            Penny,
+        ];
+        pickups.sort();
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in pickups.iter().copied() {
+            held[p] += 1;
+        }
+        let actual = PickupIterator::new(held, 8)
+            .map(|pickups| Vec::from(&pickups[..]))
+            .collect::<HashSet<_>>();
+        let correct = pickups
+            .iter()
+            .copied()
+            .combinations(8)
+            .collect::<HashSet<_>>();
+        let unexpected = actual.difference(&correct).collect::<HashSet<_>>();
+        let expected = correct.difference(&actual).collect::<HashSet<_>>();
+        assert!(
+            unexpected.is_empty() && expected.is_empty(),
+            "unexpected: {:?}\nexpected: {:?}",
+            unexpected,
+            expected
+        );
+    }
 }
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{js_item_id_to_item_id, CrafterError, InternalItemId, ItemCatalog, ItemId, Pickup};
+
+const ENGLISH: &str = "en";
+
+/// The game's string table (`stringtable.sta`): every translatable string, in every language.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StringTable {
+    /// Language codes, in the order of each key's strings.
+    languages: Vec<String>,
+    strings: HashMap<String, Vec<String>>,
+    /// English string to key, for the files that name things in English instead of by key. When
+    /// several keys have the same English string, a key of the Items category wins, then a `_NAME`
+    /// key, then the first one in the file.
+    keys_by_english: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StringTableXml {
+    #[serde(rename = "$value")]
+    entries: Vec<StringTableEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StringTableEntry {
+    Info(Other),
+    Language(Language),
+    Category(Category),
+}
+
+#[derive(Debug, Deserialize)]
+struct Other {}
+
+#[derive(Debug, Deserialize)]
+struct Language {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Category {
+    name: String,
+    #[serde(rename = "key", default)]
+    keys: Vec<Key>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Key {
+    name: String,
+    #[serde(rename = "string", default)]
+    strings: Vec<String>,
+}
+
+impl StringTable {
+    pub fn new(stringtable_sta: &str) -> Result<Self, CrafterError> {
+        let table: StringTableXml = serde_xml_rs::from_reader(stringtable_sta.as_bytes())
+            .map_err(|e| CrafterError::from_xml("stringtable.sta", e))?;
+        let mut result = StringTable::default();
+        let mut in_file_order = Vec::new();
+        for entry in table.entries {
+            match entry {
+                StringTableEntry::Info(_) => {}
+                StringTableEntry::Language(language) => result.languages.push(language.name),
+                StringTableEntry::Category(category) => {
+                    for key in category.keys {
+                        let rank = (category.name != "Items", !key.name.ends_with("_NAME"));
+                        in_file_order.push((rank, key.name.clone()));
+                        result.strings.insert(key.name, key.strings);
+                    }
+                }
+            }
+        }
+        if let Some(english) = result.language_index(ENGLISH) {
+            // a stable sort keeps the file order among keys of the same rank
+            in_file_order.sort_by_key(|(rank, _)| *rank);
+            for (_, key) in in_file_order {
+                if let Some(string) = result.strings[&key].get(english).filter(|s| !s.is_empty()) {
+                    result.keys_by_english.entry(string.clone()).or_insert(key);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.iter().map(String::as_str)
+    }
+
+    /// The string for `key` in `language`, in English if it isn't translated.
+    pub fn get(&self, key: &str, language: &str) -> Option<&str> {
+        let strings = self.strings.get(key.trim_start_matches('#'))?;
+        let translated = |language| {
+            self.language_index(language)
+                .and_then(|index| strings.get(index))
+                .map(String::as_str)
+                .filter(|s| !s.is_empty())
+        };
+        translated(language).or_else(|| translated(ENGLISH))
+    }
+
+    /// Translate an English string, as long as the table has it.
+    pub fn translate(&self, english: &str, language: &str) -> Option<&str> {
+        self.get(self.keys_by_english.get(english)?, language)
+    }
+
+    fn language_index(&self, language: &str) -> Option<usize> {
+        self.languages.iter().position(|l| l == language)
+    }
+}
+
+/// Item and pickup names in the languages of the game's string table.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Localization {
+    catalog: ItemCatalog,
+    strings: StringTable,
+    pickup_keys: HashMap<Pickup, String>,
+}
+
+#[wasm_bindgen]
+impl Localization {
+    #[wasm_bindgen(constructor)]
+    pub fn new(catalog: &ItemCatalog, stringtable_sta: &str) -> Result<Localization, CrafterError> {
+        Ok(Localization::with_strings(
+            catalog.clone(),
+            StringTable::new(stringtable_sta)?,
+        ))
+    }
+
+    /// `language` is a language code of the string table, e.g. `"jp"`.
+    #[wasm_bindgen(js_name = name)]
+    pub fn js_name(&self, item: ItemId, language: &str) -> Result<Option<String>, CrafterError> {
+        Ok(self
+            .name(js_item_id_to_item_id(item)?, language)
+            .map(str::to_owned))
+    }
+
+    #[wasm_bindgen(js_name = pickup_name)]
+    pub fn js_pickup_name(&self, pickup: Pickup, language: &str) -> String {
+        self.pickup_name(pickup, language).to_owned()
+    }
+
+    /// Name `pickup` by the string table's `key`, with or without the leading `#`. No pickup has a
+    /// key until one is set, the crate doesn't know which keys, if any, a game version uses.
+    pub fn set_pickup_key(&mut self, pickup: Pickup, key: &str) {
+        self.pickup_keys.insert(pickup, key.to_owned());
+    }
+
+    pub fn languages(&self) -> Vec<String> {
+        self.strings.languages().map(str::to_owned).collect()
+    }
+}
+
+impl Localization {
+    pub fn with_strings(catalog: ItemCatalog, strings: StringTable) -> Self {
+        Localization {
+            catalog,
+            strings,
+            pickup_keys: HashMap::new(),
+        }
+    }
+
+    /// The item's name in `language`, in English if it isn't translated. Items.xml either names an
+    /// item by a string table key starting with `#`, or in English.
+    pub fn name(&self, item: InternalItemId, language: &str) -> Option<&str> {
+        let name = self.catalog.get(item)?.name.as_str();
+        if name.starts_with('#') {
+            self.strings.get(name, language)
+        } else {
+            Some(self.strings.translate(name, language).unwrap_or(name))
+        }
+    }
+
+    /// The pickup's name in `language`, through the key set with
+    /// [`Localization::set_pickup_key`]. In English if the pickup has no key, or the key isn't
+    /// translated.
+    pub fn pickup_name(&self, pickup: Pickup, language: &str) -> &str {
+        self.pickup_keys
+            .get(&pickup)
+            .and_then(|key| self.strings.get(key, language))
+            .unwrap_or_else(|| english_name(pickup))
+    }
+}
+
+/// The pickup's name when it has no key, or the string table doesn't have the key.
+fn english_name(pickup: Pickup) -> &'static str {
+    use Pickup::*;
+    match pickup {
+        RedHeart => "Red Heart",
+        SoulHeart => "Soul Heart",
+        BlackHeart => "Black Heart",
+        EternalHeart => "Eternal Heart",
+        GoldHeart => "Gold Heart",
+        BoneHeart => "Bone Heart",
+        RottenHeart => "Rotten Heart",
+        Penny => "Penny",
+        Nickel => "Nickel",
+        Dime => "Dime",
+        LuckyPenny => "Lucky Penny",
+        Key => "Key",
+        GoldenKey => "Golden Key",
+        ChargedKey => "Charged Key",
+        Bomb => "Bomb",
+        GoldenBomb => "Golden Bomb",
+        GigaBomb => "Giga Bomb",
+        MicroBattery => "Micro Battery",
+        LilBattery => "Lil' Battery",
+        MegaBattery => "Mega Battery",
+        Card => "Card",
+        Pill => "Pill",
+        Rune => "Rune",
+        DiceShard => "Dice Shard",
+        CrackedKey => "Cracked Key",
+        GoldenPenny => "Golden Penny",
+        GoldenPill => "Golden Pill",
+        GoldenBattery => "Golden Battery",
+        PoopNugget => "Poop Nugget",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS_XML: &str = include_str!("../fixtures/items.xml");
+    const STRINGTABLE_STA: &str = include_str!("../fixtures/stringtable.sta");
+
+    fn get_localization() -> Localization {
+        Localization::new(&ItemCatalog::new(ITEMS_XML).unwrap(), STRINGTABLE_STA).unwrap()
+    }
+
+    #[test]
+    fn item_names() {
+        let localization = get_localization();
+        assert_eq!(
+            vec!["en", "jp", "ru"],
+            localization.strings.languages().collect::<Vec<_>>()
+        );
+        let sad_onion = InternalItemId(1);
+        assert_eq!(Some("The Sad Onion"), localization.name(sad_onion, "en"));
+        assert_eq!(Some("悲しい玉ねぎ"), localization.name(sad_onion, "jp"));
+        assert_eq!(Some("The Sad Onion"), localization.name(sad_onion, "kr"));
+
+        // empty translations and items missing from the table fall back to English
+        let brother_bobby = InternalItemId(8);
+        assert_eq!(
+            Some("Brother Bobby"),
+            localization.name(brother_bobby, "ru")
+        );
+        assert_eq!(Some("The D6"), localization.name(InternalItemId(105), "jp"));
+        assert_eq!(None, localization.name(InternalItemId(2), "en"));
+    }
+
+    #[test]
+    fn item_name_keys() {
+        let catalog = ItemCatalog::new(
+            r##"<items gfxroot="gfx/items/"><passive id="1" name="#THE_SAD_ONION_NAME" /></items>"##,
+        )
+        .unwrap();
+        let localization = Localization::new(&catalog, STRINGTABLE_STA).unwrap();
+        assert_eq!(
+            Some("Грустный лук"),
+            localization.name(InternalItemId(1), "ru")
+        );
+        assert_eq!(
+            Some("The Sad Onion"),
+            localization.name(InternalItemId(1), "de")
+        );
+    }
+
+    #[test]
+    fn pickup_names() {
+        // no pickup has a key until one is set
+        let localization = get_localization();
+        for pickup in [Pickup::RedHeart, Pickup::LilBattery, Pickup::DiceShard].iter() {
+            assert_eq!(
+                english_name(*pickup),
+                localization.pickup_name(*pickup, "jp")
+            );
+        }
+        assert_eq!("Lil' Battery", english_name(Pickup::LilBattery));
+    }
+
+    #[test]
+    fn pickup_name_keys() {
+        // the pickup's key is looked up, not a key whose English string matches the pickup's name
+        let strings = StringTable::new(
+            r#"<stringtable>
+                <language name="en" />
+                <language name="jp" />
+                <category name="Pickups">
+                    <key name="COIN"><string>Coin</string><string>コイン</string></key>
+                    <key name="SHOP_PENNY"><string>Penny</string><string>ペニー</string></key>
+                </category>
+            </stringtable>"#,
+        )
+        .unwrap();
+        let mut localization =
+            Localization::with_strings(ItemCatalog::new(ITEMS_XML).unwrap(), strings);
+        localization.set_pickup_key(Pickup::Penny, "#COIN");
+        localization.set_pickup_key(Pickup::Nickel, "NICKEL");
+        assert_eq!("コイン", localization.pickup_name(Pickup::Penny, "jp"));
+        assert_eq!("Coin", localization.pickup_name(Pickup::Penny, "ru"));
+        assert_eq!("Nickel", localization.pickup_name(Pickup::Nickel, "jp"));
+        assert_eq!("Dime", localization.pickup_name(Pickup::Dime, "jp"));
+    }
+
+    #[test]
+    fn same_english_string() {
+        // keys earlier in the file lose to an Items key, and to a _NAME key
+        let strings = StringTable::new(
+            r#"<stringtable>
+                <language name="en" />
+                <language name="jp" />
+                <category name="Trinkets">
+                    <key name="ONION_TRINKET_NAME"><string>Onion</string><string>トリンケット</string></key>
+                    <key name="MOMS_KEY_DESCRIPTION"><string>Mom's Key</string><string>説明</string></key>
+                    <key name="MOMS_KEY_NAME"><string>Mom's Key</string><string>名前</string></key>
+                    <key name="PENNY_NAME"><string>Penny</string><string>最初</string></key>
+                    <key name="PENNY_2_NAME"><string>Penny</string><string>二番目</string></key>
+                </category>
+                <category name="Items">
+                    <key name="ONION_DESCRIPTION"><string>Onion</string><string>アイテム</string></key>
+                </category>
+            </stringtable>"#,
+        )
+        .unwrap();
+        assert_eq!(Some("アイテム"), strings.translate("Onion", "jp"));
+        assert_eq!(Some("名前"), strings.translate("Mom's Key", "jp"));
+        assert_eq!(Some("最初"), strings.translate("Penny", "jp"));
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+use strum::IntoEnumIterator;
+use wasm_bindgen::prelude::*;
+
+use crate::{InternalItemId, ItemPool};
+
+/// Something in the run, usually a held passive item, that changes what crafting produces.
+///
+/// Every hook defaults to leaving crafting alone, so a modifier only overrides what it changes.
+/// Modifiers run in the order they were added to the crafter.
+pub trait RunModifier: Debug + Send + Sync {
+    /// Change which pools crafting rolls from and how much each weighs.
+    fn pool_weights(&self, _pool_weights: &mut Vec<(ItemPool, f32)>) {}
+
+    /// Change the qualities allowed out of `pool`.
+    fn quality_bounds(&self, _pool: ItemPool, bounds: RangeInclusive<u32>) -> RangeInclusive<u32> {
+        bounds
+    }
+
+    /// Change the rolled item, possibly rolling again through `roll`.
+    fn roll(&self, item: InternalItemId, _roll: &mut RollContext<'_>) -> InternalItemId {
+        item
+    }
+}
+
+/// What a [`RunModifier`] can look at and do while picking the crafted item.
+pub struct RollContext<'a> {
+    pub(crate) quality: &'a dyn Fn(InternalItemId) -> u32,
+    pub(crate) reroll: &'a mut dyn FnMut() -> InternalItemId,
+}
+
+impl<'a> RollContext<'a> {
+    pub fn quality(&self, item: InternalItemId) -> u32 {
+        (self.quality)(item)
+    }
+
+    /// Roll again from the same weights, advancing the rng.
+    pub fn reroll(&mut self) -> InternalItemId {
+        (self.reroll)()
+    }
+}
+
+/// Rerolls quality 0 and 1 results, like Sacred Orb does for item pedestals.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SacredOrb;
+
+impl SacredOrb {
+    const MAX_REROLLS: usize = 10;
+}
+
+impl RunModifier for SacredOrb {
+    fn roll(&self, mut item: InternalItemId, roll: &mut RollContext<'_>) -> InternalItemId {
+        for _ in 0..SacredOrb::MAX_REROLLS {
+            if roll.quality(item) >= 2 {
+                break;
+            }
+            item = roll.reroll();
+        }
+        item
+    }
+}
+
+/// Items come from any pool, like Chaos: the total weight is spread evenly over every pool.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Chaos;
+
+impl RunModifier for Chaos {
+    fn pool_weights(&self, pool_weights: &mut Vec<(ItemPool, f32)>) {
+        let pools: Vec<ItemPool> = ItemPool::iter()
+            .filter(|pool| !pool.is_greed() && *pool != ItemPool::Unused24)
+            .collect();
+        let total: f32 = pool_weights.iter().map(|(_, weight)| weight.max(0.)).sum();
+        let weight = total / pools.len() as f32;
+        *pool_weights = pools.into_iter().map(|pool| (pool, weight)).collect();
+    }
+}
+
+/// The built-in modifiers, for toggling them from JS.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BuiltinModifier {
+    SacredOrb,
+    Chaos,
+}
+
+impl BuiltinModifier {
+    pub(crate) fn modifier(self) -> Box<dyn RunModifier> {
+        match self {
+            BuiltinModifier::SacredOrb => Box::new(SacredOrb),
+            BuiltinModifier::Chaos => Box::new(Chaos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chaos_spreads_weight() {
+        let mut pool_weights = vec![
+            (ItemPool::Treasure, 1.),
+            (ItemPool::Shop, 2.),
+            (ItemPool::Boss, 2.),
+            (ItemPool::Angel, 0.),
+        ];
+        Chaos.pool_weights(&mut pool_weights);
+        assert_eq!(23, pool_weights.len());
+        assert!(pool_weights
+            .iter()
+            .all(|(pool, _)| !pool.is_greed() && *pool != ItemPool::Unused24));
+        let total: f32 = pool_weights.iter().map(|(_, weight)| weight).sum();
+        assert!((total - 5.).abs() < 0.001);
+    }
+}
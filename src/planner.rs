@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+
+use crate::{InternalItemId, InternalPickups, Pickup, SlotMap};
+
+/// One craft out of a plan: the item and the 8 pickups it is made from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PlannedCraft {
+    pub item: InternalItemId,
+    pub pickups: [Pickup; 8],
+}
+
+/// The crafts of a plan, most valuable first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftPlan {
+    pub crafts: Vec<PlannedCraft>,
+    /// False when the search ran out of its node budget, so a more valuable plan may exist.
+    pub complete: bool,
+}
+
+struct Candidate {
+    item: InternalItemId,
+    pickups: InternalPickups,
+    value: f64,
+}
+
+/// How many partial plans the search looks at before settling for the best plan it found so far.
+const NODE_BUDGET: usize = 1 << 20;
+
+/// Pick at most `max_crafts` recipes out of `methods` that can all be made from `held` at the same
+/// time, maximizing the summed value of the crafted items. Items worth nothing, or with a value
+/// that isn't finite, are never crafted.
+///
+/// The search is exact unless it runs out of its node budget, which takes many held pickups and
+/// many recipes of equal value. The plan says whether it did.
+pub(crate) fn plan_crafts<F: Fn(InternalItemId) -> f64>(
+    methods: &SlotMap<InternalItemId, HashSet<InternalPickups>>,
+    held: &SlotMap<Pickup, u8>,
+    value: F,
+    max_crafts: usize,
+) -> CraftPlan {
+    plan_crafts_with_budget(methods, held, value, max_crafts, NODE_BUDGET)
+}
+
+fn plan_crafts_with_budget<F: Fn(InternalItemId) -> f64>(
+    methods: &SlotMap<InternalItemId, HashSet<InternalPickups>>,
+    held: &SlotMap<Pickup, u8>,
+    value: F,
+    max_crafts: usize,
+    node_budget: usize,
+) -> CraftPlan {
+    let mut candidates: Vec<Candidate> = methods
+        .iter()
+        .filter(|(_, recipes)| !recipes.is_empty())
+        .map(|(item, recipes)| (item, recipes, value(item)))
+        .filter(|(_, _, value)| value.is_finite() && *value > 0.)
+        .flat_map(|(item, recipes, value)| {
+            recipes.iter().map(move |pickups| Candidate {
+                item,
+                pickups: *pickups,
+                value,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.value
+            .total_cmp(&a.value)
+            .then_with(|| a.item.cmp(&b.item))
+            .then_with(|| a.pickups.cmp(&b.pickups))
+    });
+
+    let mut search = Search {
+        candidates: candidates.as_slice(),
+        remaining: held.clone(),
+        remaining_total: held.iter().map(|(_, count)| *count as usize).sum(),
+        nodes_left: node_budget,
+        out_of_budget: false,
+        current: Vec::with_capacity(max_crafts),
+        current_value: 0.,
+        best: Vec::new(),
+        best_value: 0.,
+    };
+    search.run(0, max_crafts);
+
+    CraftPlan {
+        crafts: search
+            .best
+            .into_iter()
+            .map(|idx| PlannedCraft {
+                item: candidates[idx].item,
+                pickups: candidates[idx].pickups.pickups(),
+            })
+            .collect(),
+        complete: !search.out_of_budget,
+    }
+}
+
+struct Search<'a> {
+    candidates: &'a [Candidate],
+    remaining: SlotMap<Pickup, u8>,
+    remaining_total: usize,
+    nodes_left: usize,
+    out_of_budget: bool,
+    current: Vec<usize>,
+    current_value: f64,
+    best: Vec<usize>,
+    best_value: f64,
+}
+
+impl<'a> Search<'a> {
+    fn run(&mut self, start: usize, crafts_left: usize) {
+        if self.current_value > self.best_value {
+            self.best_value = self.current_value;
+            self.best = self.current.clone();
+        }
+        // every craft takes 8 pickups, a bound that matters when lots of plans are worth the same
+        let crafts_left = crafts_left.min(self.remaining_total / 8);
+        if crafts_left == 0 {
+            return;
+        }
+        if self.nodes_left == 0 {
+            self.out_of_budget = true;
+            return;
+        }
+        self.nodes_left -= 1;
+
+        for idx in start..self.candidates.len() {
+            let candidate = &self.candidates[idx];
+            // candidates are sorted by value, nothing after this one can beat the best plan
+            if self.current_value + candidate.value * crafts_left as f64 <= self.best_value {
+                break;
+            }
+            if !self.take(candidate.pickups) {
+                continue;
+            }
+            self.current.push(idx);
+            self.current_value += candidate.value;
+            // the same recipe can be crafted again if enough pickups are held
+            self.run(idx, crafts_left - 1);
+            self.current_value -= candidate.value;
+            self.current.pop();
+            self.give_back(candidate.pickups);
+        }
+    }
+
+    fn take(&mut self, pickups: InternalPickups) -> bool {
+        let pickups = pickups.pickups();
+        for (i, p) in pickups.iter().copied().enumerate() {
+            if self.remaining[p] == 0 {
+                for p in pickups[..i].iter().copied() {
+                    self.remaining[p] += 1;
+                }
+                return false;
+            }
+            self.remaining[p] -= 1;
+        }
+        self.remaining_total -= 8;
+        true
+    }
+
+    fn give_back(&mut self, pickups: InternalPickups) {
+        for p in pickups.pickups().iter().copied() {
+            self.remaining[p] += 1;
+        }
+        self.remaining_total += 8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Pickup::*;
+
+    use crate::DeltaCrafter;
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+
+    fn get_delta_crafter(pickups: &[Pickup]) -> DeltaCrafter {
+        let mut delta_crafter = DeltaCrafter::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        for p in pickups.iter().copied() {
+            delta_crafter.add_pickup(p);
+        }
+        delta_crafter
+    }
+
+    #[test]
+    fn crafts_are_disjoint() {
+        let mut pickups = vec![SoulHeart; 14];
+        pickups.extend_from_slice(&[EternalHeart, LuckyPenny]);
+        let delta_crafter = get_delta_crafter(pickups.as_slice());
+        let wishlist = [InternalItemId(114), InternalItemId(363)];
+        let plan = delta_crafter.best_crafts(
+            |item| {
+                if wishlist.contains(&item) {
+                    1.
+                } else {
+                    0.
+                }
+            },
+            2,
+        );
+
+        assert!(plan.complete);
+        assert_eq!(2, plan.crafts.len());
+        let mut used = SlotMap::<Pickup, u8>::default();
+        for craft in plan.crafts.iter() {
+            assert!(wishlist.contains(&craft.item));
+            for p in craft.pickups.iter().copied() {
+                used[p] += 1;
+            }
+        }
+        assert_eq!(14, used[SoulHeart]);
+        assert_eq!(1, used[EternalHeart]);
+        assert_eq!(1, used[LuckyPenny]);
+    }
+
+    #[test]
+    fn same_recipe_twice() {
+        let delta_crafter = get_delta_crafter(&[SoulHeart; 16]);
+        let plan = delta_crafter.best_crafts(|item| (item == InternalItemId(114)) as u8 as f64, 3);
+        assert_eq!(
+            vec![
+                PlannedCraft {
+                    item: InternalItemId(114),
+                    pickups: [SoulHeart; 8],
+                };
+                2
+            ],
+            plan.crafts
+        );
+    }
+
+    #[test]
+    fn non_finite_values() {
+        let delta_crafter = get_delta_crafter(&[SoulHeart; 16]);
+        let plan = delta_crafter.best_crafts(
+            |item| {
+                if item == InternalItemId(114) {
+                    f64::NAN
+                } else {
+                    f64::INFINITY
+                }
+            },
+            2,
+        );
+        assert!(plan.crafts.is_empty());
+    }
+
+    #[test]
+    fn many_pickups_tied_values() {
+        let delta_crafter = get_delta_crafter(&[
+            RedHeart, RedHeart, RedHeart, SoulHeart, SoulHeart, BlackHeart, GoldHeart, Penny,
+            Penny, Nickel, Nickel, Dime, LuckyPenny, Key, Key, Bomb, Bomb, Card, Card, Pill, Pill,
+            Rune, Rune, LilBattery,
+        ]);
+        let quality = |item| delta_crafter.crafter().item_quality(item);
+        let values: [&dyn Fn(InternalItemId) -> f64; 2] = [&|item| quality(item) as f64, &|item| {
+            (quality(item) == 4) as u8 as f64
+        }];
+        for value in values.iter() {
+            let plan = delta_crafter.best_crafts(value, 3);
+            assert!(plan.complete);
+            assert_eq!(3, plan.crafts.len());
+            let mut used = SlotMap::<Pickup, u8>::default();
+            for p in plan.crafts.iter().flat_map(|craft| craft.pickups.iter()) {
+                used[*p] += 1;
+            }
+            assert_eq!(delta_crafter.held, used);
+        }
+    }
+
+    #[test]
+    fn out_of_budget() {
+        let delta_crafter = get_delta_crafter(&[SoulHeart; 16]);
+        let value = |item| (item == InternalItemId(114)) as u8 as f64;
+        let plan =
+            plan_crafts_with_budget(&delta_crafter.methods, &delta_crafter.held, value, 2, 1);
+        assert!(!plan.complete);
+        assert_eq!(1, plan.crafts.len());
+        assert!(
+            plan_crafts_with_budget(&delta_crafter.methods, &delta_crafter.held, value, 2, 2)
+                .complete
+        );
+    }
+
+    #[test]
+    fn best_by_quality() {
+        let delta_crafter = get_delta_crafter(&[
+            RedHeart, RedHeart, SoulHeart, Penny, Penny, Nickel, LuckyPenny, Key, LilBattery, Card,
+            Rune, Bomb,
+        ]);
+        let plan =
+            delta_crafter.best_crafts(|item| delta_crafter.crafter().item_quality(item) as f64, 1);
+        assert_eq!(1, plan.crafts.len());
+        let best_quality = delta_crafter
+            .methods
+            .iter()
+            .filter(|(_, m)| !m.is_empty())
+            .map(|(item, _)| delta_crafter.crafter().item_quality(item))
+            .max()
+            .unwrap();
+        assert_eq!(
+            best_quality,
+            delta_crafter.crafter().item_quality(plan.crafts[0].item)
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::{get_pool_item_weights, CrafterError, InternalItemId, ItemPool};
+
+/// An item's entry in a pool, as listed in itempools.xml.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct PoolItem {
+    pub(crate) weight: f32,
+    /// How much the weight drops each time the item is rolled from the pool.
+    pub(crate) decrease_by: f32,
+    /// The item leaves the pool once its weight drops below this.
+    pub(crate) remove_on: f32,
+}
+
+/// The item pools as they stand at some point of a run, after items have been rolled and picked up.
+#[derive(Debug, Clone)]
+pub struct PoolState {
+    pools: HashMap<ItemPool, HashMap<InternalItemId, PoolItem>>,
+    initial: HashMap<ItemPool, HashMap<InternalItemId, PoolItem>>,
+}
+
+impl PoolState {
+    /// The pools at the start of a run.
+    pub fn new(itempools_xml: &str) -> Result<Self, CrafterError> {
+        Ok(PoolState::from_pools(get_pool_item_weights(
+            itempools_xml,
+            &HashMap::new(),
+        )?))
+    }
+
+    pub(crate) fn from_pools(pools: HashMap<ItemPool, HashMap<InternalItemId, PoolItem>>) -> Self {
+        PoolState {
+            initial: pools.clone(),
+            pools,
+        }
+    }
+
+    /// `item` was rolled from `pool`: its weight in that pool drops by its `DecreaseBy`, and it is
+    /// removed from the pool once the weight is below its `RemoveOn`.
+    ///
+    /// Returns whether the pool changed.
+    pub fn item_seen(&mut self, item: InternalItemId, pool: ItemPool) -> bool {
+        let items = match self.pools.get_mut(&pool) {
+            Some(items) => items,
+            None => return false,
+        };
+        let entry = match items.get_mut(&item) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        entry.weight -= entry.decrease_by;
+        if entry.weight < entry.remove_on {
+            items.remove(&item);
+        }
+        true
+    }
+
+    /// `item` was picked up, so no pool can give it again this run.
+    ///
+    /// Returns whether any pool changed.
+    pub fn item_taken(&mut self, item: InternalItemId) -> bool {
+        let mut changed = false;
+        for items in self.pools.values_mut() {
+            changed |= items.remove(&item).is_some();
+        }
+        changed
+    }
+
+    /// Back to the pools at the start of a run.
+    pub fn reset(&mut self) {
+        self.pools = self.initial.clone();
+    }
+
+    /// The current weight of `item` in `pool`, `None` if it isn't in the pool (anymore).
+    pub fn weight(&self, pool: ItemPool, item: InternalItemId) -> Option<f32> {
+        self.pools
+            .get(&pool)
+            .and_then(|items| items.get(&item))
+            .map(|entry| entry.weight)
+    }
+
+    /// The largest item id any pool started the run with.
+    pub fn largest_item(&self) -> InternalItemId {
+        self.initial
+            .values()
+            .flat_map(|items| items.keys())
+            .copied()
+            .max()
+            .unwrap_or(InternalItemId::from(0_u16))
+    }
+
+    /// Nothing for a pool the data doesn't have, so it can't be rolled from.
+    pub(crate) fn items(
+        &self,
+        pool: ItemPool,
+    ) -> impl Iterator<Item = (&InternalItemId, &PoolItem)> {
+        self.pools.get(&pool).into_iter().flatten()
+    }
+
+    pub(crate) fn pools(
+        &self,
+    ) -> impl Iterator<Item = (ItemPool, &HashMap<InternalItemId, PoolItem>)> {
+        self.pools.iter().map(|(pool, items)| (*pool, items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+
+    #[test]
+    fn seen_decreases_then_removes() {
+        let mut state = PoolState::new(ITEMPOOLS_XML).unwrap();
+        let sad_onion = InternalItemId(1);
+        assert_eq!(Some(1.), state.weight(ItemPool::Treasure, sad_onion));
+        assert!(state.item_seen(sad_onion, ItemPool::Treasure));
+        assert_eq!(None, state.weight(ItemPool::Treasure, sad_onion));
+        assert!(!state.item_seen(sad_onion, ItemPool::Treasure));
+
+        state.reset();
+        assert_eq!(Some(1.), state.weight(ItemPool::Treasure, sad_onion));
+    }
+
+    #[test]
+    fn seen_decreases_partially() {
+        let mut state = PoolState::new(ITEMPOOLS_XML).unwrap();
+        let (pool, item, entry) = state
+            .pools
+            .iter()
+            .flat_map(|(pool, items)| items.iter().map(move |(item, e)| (*pool, *item, *e)))
+            .find(|(_, _, e)| e.weight - e.decrease_by >= e.remove_on)
+            .unwrap();
+        assert!(state.item_seen(item, pool));
+        assert_eq!(
+            Some(entry.weight - entry.decrease_by),
+            state.weight(pool, item)
+        );
+    }
+
+    #[test]
+    fn taken_removes_everywhere() {
+        let mut state = PoolState::new(ITEMPOOLS_XML).unwrap();
+        let sacred_heart = InternalItemId(182);
+        assert!(state.weight(ItemPool::Angel, sacred_heart).is_some());
+        assert!(state.item_taken(sacred_heart));
+        assert!(ItemPool::iter().all(|pool| state.weight(pool, sacred_heart).is_none()));
+        assert!(!state.item_taken(sacred_heart));
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BasicCrafter, Crafter, InternalItemId, InternalPickups, Pickup, PickupIterator, SlotMap,
+};
+
+/// The recipes that craft something else under a new crafter configuration, e.g. after a game
+/// update changed the item pools or the crafting rules.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecipeDiff {
+    /// How many recipes were crafted with both crafters.
+    pub recipes_compared: usize,
+    /// Sorted by old item, then new item.
+    pub changes: Vec<RecipeChange>,
+    /// Sorted by item.
+    pub items: Vec<ItemChange>,
+}
+
+/// Every recipe that used to craft `old` and now crafts `new`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecipeChange {
+    pub old: InternalItemId,
+    pub new: InternalItemId,
+    pub recipes: Vec<[Pickup; 8]>,
+}
+
+/// How many recipes an item gained and lost.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemChange {
+    pub item: InternalItemId,
+    pub gained: usize,
+    pub lost: usize,
+}
+
+impl RecipeDiff {
+    /// Compare every recipe of 8 pickups that can be made out of `held`.
+    ///
+    /// Holding 8 of every pickup covers the whole recipe space, which is tens of millions of
+    /// recipes, so it's usually worth bounding it to the pickups the update could have affected.
+    pub fn between(old: &BasicCrafter, new: &BasicCrafter, held: &[(Pickup, u8)]) -> Self {
+        let mut held_pickups = SlotMap::<Pickup, u8>::default();
+        for (pickup, count) in held {
+            held_pickups[*pickup] = held_pickups[*pickup].saturating_add(*count);
+        }
+
+        let mut recipes_compared = 0;
+        let mut changes = BTreeMap::<(InternalItemId, InternalItemId), Vec<[Pickup; 8]>>::new();
+        let mut items = BTreeMap::<InternalItemId, ItemChange>::new();
+        let recipes: Box<dyn Iterator<Item = InternalPickups>> =
+            if held_pickups.iter().all(|(_, count)| *count >= 8) {
+                Box::new(InternalPickups::all())
+            } else {
+                Box::new(PickupIterator::new(held_pickups, 8).map(InternalPickups::new))
+            };
+        for pickups in recipes {
+            recipes_compared += 1;
+            let old_item = old.craft(pickups);
+            let new_item = new.craft(pickups);
+            if old_item == new_item {
+                continue;
+            }
+            changes
+                .entry((old_item, new_item))
+                .or_default()
+                .push(pickups.pickups());
+            item_change(&mut items, old_item).lost += 1;
+            item_change(&mut items, new_item).gained += 1;
+        }
+
+        RecipeDiff {
+            recipes_compared,
+            changes: changes
+                .into_iter()
+                .map(|((old, new), recipes)| RecipeChange { old, new, recipes })
+                .collect(),
+            items: items.into_values().collect(),
+        }
+    }
+
+    pub fn recipes_changed(&self) -> usize {
+        self.changes.iter().map(|change| change.recipes.len()).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn item_change(
+    items: &mut BTreeMap<InternalItemId, ItemChange>,
+    item: InternalItemId,
+) -> &mut ItemChange {
+    items.entry(item).or_insert(ItemChange {
+        item,
+        gained: 0,
+        lost: 0,
+    })
+}
+
+impl Display for RecipeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} of {} recipes changed",
+            self.recipes_changed(),
+            self.recipes_compared
+        )?;
+        for change in &self.changes {
+            writeln!(
+                f,
+                "\nitem {} -> item {} ({} recipes)",
+                u16::from(change.old),
+                u16::from(change.new),
+                change.recipes.len()
+            )?;
+            for recipe in &change.recipes {
+                let names: Vec<String> = recipe.iter().map(|p| format!("{:?}", p)).collect();
+                writeln!(f, "    {}", names.join(", "))?;
+            }
+        }
+        if !self.items.is_empty() {
+            writeln!(f)?;
+        }
+        for item in &self.items {
+            writeln!(
+                f,
+                "item {}: +{} -{}",
+                u16::from(item.item),
+                item.gained,
+                item.lost
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ItemPool;
+    use Pickup::*;
+
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+
+    fn get_crafter() -> BasicCrafter {
+        BasicCrafter::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap()
+    }
+
+    const HELD: &[(Pickup, u8)] = &[(RedHeart, 8), (SoulHeart, 8), (Penny, 8), (Key, 8)];
+
+    #[test]
+    fn same_crafter() {
+        let crafter = get_crafter();
+        let diff = RecipeDiff::between(&crafter, &crafter, HELD);
+        assert_eq!(165, diff.recipes_compared);
+        assert_eq!(0, diff.recipes_changed());
+        assert!(diff.changes.is_empty());
+        assert!(diff.items.is_empty());
+    }
+
+    #[test]
+    fn changed_rules() {
+        let old = get_crafter();
+        let mut new = old.clone();
+        let mut rules = new.rules().clone();
+        rules.pools.retain(|rule| rule.pool == ItemPool::Boss);
+        new.set_rules(rules);
+
+        let diff = RecipeDiff::between(&old, &new, HELD);
+        assert_ne!(0, diff.recipes_changed());
+        for change in &diff.changes {
+            assert_ne!(change.old, change.new);
+            for recipe in &change.recipes {
+                let pickups = InternalPickups::new(*recipe);
+                assert_eq!(change.old, old.craft(pickups));
+                assert_eq!(change.new, new.craft(pickups));
+            }
+        }
+        let gained: usize = diff.items.iter().map(|item| item.gained).sum();
+        let lost: usize = diff.items.iter().map(|item| item.lost).sum();
+        assert_eq!(diff.recipes_changed(), gained);
+        assert_eq!(diff.recipes_changed(), lost);
+
+        let json: RecipeDiff = serde_json::from_str(&diff.to_json()).unwrap();
+        assert_eq!(diff, json);
+        assert!(diff.to_string().starts_with(&format!(
+            "{} of 165 recipes changed",
+            diff.recipes_changed()
+        )));
+    }
+}
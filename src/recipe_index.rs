@@ -0,0 +1,217 @@
+use std::ops::Range;
+
+use strum::EnumCount;
+
+use crate::{InternalPickups, Pickup};
+
+/// How many different pickups there are.
+const PICKUPS: usize = Pickup::COUNT;
+
+/// `MULTISETS[k][n]` is the number of sorted `k` pickup recipes made only of the last `n` pickups.
+const MULTISETS: [[u32; PICKUPS + 1]; 9] = multisets();
+
+const fn multisets() -> [[u32; PICKUPS + 1]; 9] {
+    let mut multisets = [[0; PICKUPS + 1]; 9];
+    let mut n = 0;
+    while n <= PICKUPS {
+        multisets[0][n] = 1;
+        n += 1;
+    }
+    let mut k = 1;
+    while k < multisets.len() {
+        let mut n = 1;
+        while n <= PICKUPS {
+            // the recipes without the first of the n pickups, and those with it at least once
+            multisets[k][n] = multisets[k][n - 1] + multisets[k - 1][n];
+            n += 1;
+        }
+        k += 1;
+    }
+    multisets
+}
+
+/// The number of recipes before the first one starting with `pickup` at position `slot`, out of
+/// those starting with pickups from `from` onwards there.
+fn skipped(slot: usize, from: usize, pickup: usize) -> u32 {
+    (from..pickup)
+        .map(|p| MULTISETS[7 - slot][PICKUPS - p])
+        .sum()
+}
+
+impl InternalPickups {
+    /// The number of different recipes, every index is below this: 30,260,340 with 29 pickups.
+    pub(crate) const COUNT: u32 = MULTISETS[8][PICKUPS];
+
+    /// The position of the recipe among every recipe, in the order recipes compare in.
+    pub(crate) fn index(self) -> u32 {
+        let mut index = 0;
+        let mut previous = 0;
+        for (slot, pickup) in self.pickups().iter().enumerate() {
+            let pickup = usize::from(*pickup);
+            index += skipped(slot, previous, pickup);
+            previous = pickup;
+        }
+        index
+    }
+
+    /// The recipe at `index`, `None` if it's not below [`InternalPickups::COUNT`].
+    pub(crate) fn from_index(mut index: u32) -> Option<Self> {
+        if index >= InternalPickups::COUNT {
+            return None;
+        }
+        let mut pickups = [Pickup::RedHeart; 8];
+        let mut pickup = 0;
+        for (slot, p) in pickups.iter_mut().enumerate() {
+            while index >= MULTISETS[7 - slot][PICKUPS - pickup] {
+                index -= MULTISETS[7 - slot][PICKUPS - pickup];
+                pickup += 1;
+            }
+            *p = Pickup::from(pickup);
+        }
+        Some(InternalPickups::new(pickups))
+    }
+
+    /// The recipes at `indices`, in order.
+    pub(crate) fn range(indices: Range<u32>) -> IndexRange {
+        let end = indices.end.min(InternalPickups::COUNT);
+        IndexRange {
+            next: InternalPickups::from_index(indices.start).map(InternalPickups::pickups),
+            remaining: end.saturating_sub(indices.start),
+        }
+    }
+
+    /// Every recipe, in order.
+    pub(crate) fn all() -> IndexRange {
+        InternalPickups::range(0..InternalPickups::COUNT)
+    }
+}
+
+/// The number of different recipes of 8 pickups, every recipe index is below this. With the 29
+/// pickups there are now, that is 36 choose 8, or 30,260,340.
+pub const RECIPE_COUNT: u32 = InternalPickups::COUNT;
+
+/// The position of a recipe, in any order, among every recipe sorted by pickup.
+pub fn recipe_index(pickups: [Pickup; 8]) -> u32 {
+    InternalPickups::new(pickups).index()
+}
+
+/// The sorted recipe at `index`, `None` if it's not below [`RECIPE_COUNT`].
+pub fn recipe_at(index: u32) -> Option<[Pickup; 8]> {
+    InternalPickups::from_index(index).map(InternalPickups::pickups)
+}
+
+/// The sorted recipes at `indices`, in order. Indices from [`RECIPE_COUNT`] on are left out.
+pub fn recipes(indices: Range<u32>) -> impl ExactSizeIterator<Item = [Pickup; 8]> {
+    InternalPickups::range(indices).map(InternalPickups::pickups)
+}
+
+/// The recipes of a range of indices, see [`InternalPickups::range`].
+#[derive(Debug, Clone)]
+pub(crate) struct IndexRange {
+    next: Option<[Pickup; 8]>,
+    remaining: u32,
+}
+
+impl Iterator for IndexRange {
+    type Item = InternalPickups;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let mut pickups = self.next?;
+        let recipe = InternalPickups::new(pickups);
+        self.remaining -= 1;
+
+        // the next recipe bumps the last pickup that isn't the last one, and repeats it after
+        let last = Pickup::from(PICKUPS - 1);
+        self.next = pickups.iter().rposition(|p| *p != last).map(|slot| {
+            let bumped = Pickup::from(usize::from(pickups[slot]) + 1);
+            for p in pickups[slot..].iter_mut() {
+                *p = bumped;
+            }
+            pickups
+        });
+        Some(recipe)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for IndexRange {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use strum::IntoEnumIterator;
+
+    use crate::{PickupIterator, SlotMap};
+
+    use super::*;
+
+    #[test]
+    fn count() {
+        assert_eq!(PICKUPS, Pickup::iter().count());
+        // 8 out of 29 with repetition is 36 choose 8
+        assert_eq!(30_260_340, RECIPE_COUNT);
+
+        // a smaller space, where every recipe can be listed another way
+        let mut held = SlotMap::<Pickup, u8>::default();
+        for p in [
+            Pickup::RedHeart,
+            Pickup::Penny,
+            Pickup::Rune,
+            Pickup::PoopNugget,
+        ]
+        .iter()
+        {
+            held[*p] = 8;
+        }
+        let mut indices: Vec<u32> = PickupIterator::new(held, 8).map(recipe_index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(165, indices.len());
+        for index in indices {
+            assert_eq!(Some(index), recipe_at(index).map(recipe_index));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let all = InternalPickups::all();
+        assert_eq!(InternalPickups::COUNT as usize, all.len());
+        for (index, recipe) in all.step_by(997).enumerate() {
+            let index = index as u32 * 997;
+            assert_eq!(index, recipe.index());
+            assert_eq!(Some(recipe), InternalPickups::from_index(index));
+        }
+
+        let last = InternalPickups::new([Pickup::PoopNugget; 8]);
+        assert_eq!(InternalPickups::COUNT - 1, last.index());
+        assert_eq!(0, InternalPickups::new([Pickup::RedHeart; 8]).index());
+        assert_eq!(None, InternalPickups::from_index(InternalPickups::COUNT));
+    }
+
+    #[test]
+    fn ranges() {
+        let start = InternalPickups::COUNT - 5000;
+        let range: Vec<_> = InternalPickups::range(start..InternalPickups::COUNT + 10).collect();
+        assert_eq!(5000, range.len());
+        assert!(range.iter().tuple_windows().all(|(a, b)| a < b));
+        for (offset, recipe) in range.iter().enumerate() {
+            assert_eq!(start + offset as u32, recipe.index());
+        }
+
+        assert_eq!(0, recipes(10..10).count());
+        assert_eq!(
+            Some(range[1].pickups()),
+            recipes(start + 1..start + 2).next()
+        );
+        assert_eq!(
+            0,
+            InternalPickups::range(InternalPickups::COUNT..u32::MAX).count()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::{BasicCrafter, InternalItemId, ItemPool};
+
+/// The items of a pool whose quality is in some range, sorted by id, with the running total of
+/// their weights.
+#[derive(Debug, Clone)]
+struct PoolTable {
+    items: Vec<InternalItemId>,
+    /// `cumulative[i]` is the weight of `items[..i]`, so it has one more entry than `items`.
+    cumulative: Vec<f64>,
+}
+
+impl PoolTable {
+    /// The weight of every item up to and including `item`.
+    fn weight_through(&self, item: InternalItemId) -> f64 {
+        self.cumulative[self.items.partition_point(|i| *i <= item)]
+    }
+
+    fn total(&self) -> f64 {
+        self.cumulative[self.items.len()]
+    }
+}
+
+/// A [`PoolTable`] for every pool and every quality range of the crafting rules, built from the
+/// state of a [`BasicCrafter`] and thrown away whenever that state changes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RollTables {
+    tables: HashMap<(ItemPool, u32, u32), PoolTable>,
+}
+
+impl RollTables {
+    pub(crate) fn new(crafter: &BasicCrafter) -> Self {
+        let mut ranges: Vec<RangeInclusive<u32>> = crafter
+            .rules()
+            .quality_bounds
+            .iter()
+            .map(|bound| bound.min..=bound.max)
+            .collect();
+        ranges.push(0..=0);
+
+        let mut tables = HashMap::new();
+        for (pool, items) in crafter.pool_state().pools() {
+            let mut items: Vec<(InternalItemId, f32)> = items
+                .iter()
+                .filter(|(item, _)| !crafter.is_excluded(**item))
+                .map(|(item, entry)| (*item, entry.weight))
+                .collect();
+            items.sort_by_key(|(item, _)| *item);
+            for range in ranges.iter() {
+                let mut table = PoolTable {
+                    items: Vec::new(),
+                    cumulative: vec![0.],
+                };
+                let mut total = 0_f64;
+                for (item, weight) in items.iter() {
+                    if range.contains(&crafter.item_quality(*item)) {
+                        total += *weight as f64;
+                        table.items.push(*item);
+                        table.cumulative.push(total);
+                    }
+                }
+                tables.insert((pool, *range.start(), *range.end()), table);
+            }
+        }
+        RollTables { tables }
+    }
+
+    /// The weights of a craft rolling from `pools`, `None` if one of them has no table.
+    pub(crate) fn weights(
+        &self,
+        pools: &[(ItemPool, f32, RangeInclusive<u32>)],
+    ) -> Option<CraftWeights<'_>> {
+        let mut weights = CraftWeights {
+            pools: Vec::with_capacity(pools.len()),
+            total: 0.,
+            error: 0.,
+            largest: InternalItemId::from(0_u16),
+        };
+        let mut entries = 0;
+        for (pool, pool_weight, range) in pools {
+            let table = self.tables.get(&(*pool, *range.start(), *range.end()))?;
+            weights.total += *pool_weight as f64 * table.total();
+            if let Some(largest) = table.items.last() {
+                weights.largest = weights.largest.max(*largest);
+            }
+            entries += table.items.len();
+            weights.pools.push((*pool_weight as f64, table));
+        }
+
+        // BasicCrafter::craft works in f32: it sums up every pool entry, scales the roll by the
+        // sum, then walks the items subtracting their weight until the roll drops below 0. Each of
+        // those operations rounds by at most half an f32 epsilon of a value no bigger than the
+        // total, so this bounds how far its walk can be from the exact sums here.
+        let operations = 2 * entries + 2 * pools.len() + 8;
+        weights.error = operations as f64 * f32::EPSILON as f64 / 2. * weights.total * 1.1;
+        Some(weights)
+    }
+}
+
+/// The pools one craft rolls from, with their pool weights applied.
+pub(crate) struct CraftWeights<'a> {
+    pools: Vec<(f64, &'a PoolTable)>,
+    total: f64,
+    /// How far from the exact weights the f32 arithmetic of a linear walk can drift.
+    error: f64,
+    largest: InternalItemId,
+}
+
+impl CraftWeights<'_> {
+    fn weight_through(&self, item: InternalItemId) -> f64 {
+        self.pools
+            .iter()
+            .map(|(pool_weight, table)| pool_weight * table.weight_through(item))
+            .sum()
+    }
+
+    /// The item `roll` (in `0..1`) lands on, `Some(None)` if no item can be rolled.
+    ///
+    /// `None` if the roll lands too close to where one item's weight ends and the next begins to
+    /// be sure the f32 walk of [`BasicCrafter::craft`] lands on the same item.
+    pub(crate) fn roll(&self, roll: f32) -> Option<Option<InternalItemId>> {
+        if self.total <= 0. {
+            return Some(None);
+        }
+        let target = roll as f64 * self.total;
+
+        // the first item whose running weight is above the target
+        let (mut low, mut high) = (0_usize, usize::from(self.largest));
+        while low < high {
+            let middle = (low + high) / 2;
+            if self.weight_through(InternalItemId::from(middle)) > target {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        let item = InternalItemId::from(low);
+        let before = match low {
+            0 => 0.,
+            _ => self.weight_through(InternalItemId::from(low - 1)),
+        };
+        let through = self.weight_through(item);
+        if target - before >= self.error && through - target > self.error {
+            Some(Some(item))
+        } else {
+            None
+        }
+    }
+}
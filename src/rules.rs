@@ -0,0 +1,226 @@
+use std::ops::RangeInclusive;
+
+use crate::{CrafterError, InternalItemId, ItemPool, Pickup};
+use serde::{Deserialize, Serialize};
+
+/// How a craft turns its pickups into pool weights and a quality range. [`Default`] gives the
+/// rules of the current game version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CraftingRules {
+    /// Every pool a craft can roll from, in the order the game adds them up.
+    pub pools: Vec<PoolRule>,
+    /// Added to the summed pickup weights before picking the quality range of a pool.
+    #[serde(default)]
+    pub quality_offsets: Vec<QualityOffset>,
+    /// Checked in order, the first one the summed pickup weights are above is used.
+    pub quality_bounds: Vec<QualityBound>,
+    /// What the bag makes when no item can be rolled.
+    pub fallback_item: InternalItemId,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolRule {
+    pub pool: ItemPool,
+    #[serde(default)]
+    pub weight: f32,
+    /// Weight added for each pickup of a kind in the recipe.
+    #[serde(default)]
+    pub per_pickup: Option<PickupWeight>,
+    /// The pool isn't rolled from at all when the recipe holds any of these.
+    #[serde(default)]
+    pub only_without: Vec<Pickup>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PickupWeight {
+    pub pickup: Pickup,
+    pub weight: f32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QualityOffset {
+    pub pool: ItemPool,
+    pub offset: i32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QualityBound {
+    pub above: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Default for CraftingRules {
+    fn default() -> Self {
+        fn fixed(pool: ItemPool, weight: f32) -> PoolRule {
+            PoolRule {
+                pool,
+                weight,
+                per_pickup: None,
+                only_without: Vec::new(),
+            }
+        }
+
+        fn per_pickup(pool: ItemPool, pickup: Pickup, weight: f32) -> PoolRule {
+            PoolRule {
+                pool,
+                weight: 0.,
+                per_pickup: Some(PickupWeight { pickup, weight }),
+                only_without: Vec::new(),
+            }
+        }
+
+        fn bound(above: u32, min: u32, max: u32) -> QualityBound {
+            QualityBound { above, min, max }
+        }
+
+        CraftingRules {
+            pools: vec![
+                fixed(ItemPool::Treasure, 1.),
+                fixed(ItemPool::Shop, 2.),
+                fixed(ItemPool::Boss, 2.),
+                per_pickup(ItemPool::Angel, Pickup::EternalHeart, 10.),
+                per_pickup(ItemPool::Devil, Pickup::BlackHeart, 10.),
+                per_pickup(ItemPool::Secret, Pickup::BoneHeart, 5.),
+                per_pickup(ItemPool::GoldenChest, Pickup::GoldHeart, 10.),
+                per_pickup(ItemPool::Curse, Pickup::RottenHeart, 10.),
+                per_pickup(ItemPool::RedChest, Pickup::CrackedKey, 10.),
+                // golden pennies, golden pills, golden batteries and poop nuggets only count
+                // towards the quality check and the rng, they don't weight any pool and don't
+                // block the planetarium
+                PoolRule {
+                    only_without: vec![Pickup::RedHeart, Pickup::Penny, Pickup::Key, Pickup::Bomb],
+                    ..per_pickup(ItemPool::Planetarium, Pickup::Rune, 10.)
+                },
+            ],
+            quality_offsets: vec![
+                QualityOffset {
+                    pool: ItemPool::Devil,
+                    offset: -5,
+                },
+                QualityOffset {
+                    pool: ItemPool::Angel,
+                    offset: -5,
+                },
+                QualityOffset {
+                    pool: ItemPool::Secret,
+                    offset: -5,
+                },
+            ],
+            quality_bounds: vec![
+                bound(34, 4, 4),
+                bound(30, 3, 4),
+                bound(26, 2, 4),
+                bound(22, 1, 4),
+                bound(18, 1, 3),
+                bound(14, 1, 2),
+                bound(8, 0, 2),
+                bound(0, 0, 1),
+            ],
+            fallback_item: InternalItemId::from(25_u16),
+        }
+    }
+}
+
+impl CraftingRules {
+    pub fn from_json(rules_json: &str) -> Result<Self, CrafterError> {
+        serde_json::from_str(rules_json).map_err(|e| CrafterError::Rules(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// The pools a recipe with `pickup_count` of each pickup rolls from, and their weights.
+    pub(crate) fn pool_weights<F: Fn(Pickup) -> u32>(
+        &self,
+        pickup_count: F,
+    ) -> Vec<(ItemPool, f32)> {
+        self.pools
+            .iter()
+            .filter(|rule| rule.only_without.iter().all(|p| pickup_count(*p) == 0))
+            .map(|rule| {
+                let weight = match rule.per_pickup {
+                    Some(per_pickup) => {
+                        rule.weight + pickup_count(per_pickup.pickup) as f32 * per_pickup.weight
+                    }
+                    None => rule.weight,
+                };
+                (rule.pool, weight)
+            })
+            .collect()
+    }
+
+    /// The qualities `pool` can give for a recipe whose pickup weights add up to `pickup_weight`.
+    pub(crate) fn quality_bounds(&self, pool: ItemPool, pickup_weight: u32) -> RangeInclusive<u32> {
+        let offset: i64 = self
+            .quality_offsets
+            .iter()
+            .filter(|o| o.pool == pool)
+            .map(|o| o.offset as i64)
+            .sum();
+        let quality_check_val = pickup_weight as i64 + offset;
+        self.quality_bounds
+            .iter()
+            .find(|bound| quality_check_val > bound.above as i64)
+            .map_or(0..=0, |bound| bound.min..=bound.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let rules = CraftingRules::default();
+        assert_eq!(rules, CraftingRules::from_json(&rules.to_json()).unwrap());
+    }
+
+    #[test]
+    fn from_json() {
+        let rules = CraftingRules::from_json(
+            r#"{
+                "pools": [
+                    { "pool": "Treasure", "weight": 1 },
+                    { "pool": "Angel", "per_pickup": { "pickup": "EternalHeart", "weight": 10 } },
+                    {
+                        "pool": "Planetarium",
+                        "per_pickup": { "pickup": "Rune", "weight": 10 },
+                        "only_without": ["Penny"]
+                    }
+                ],
+                "quality_bounds": [{ "above": 10, "min": 2, "max": 4 }],
+                "fallback_item": 25
+            }"#,
+        )
+        .unwrap();
+
+        let counts = |p: Pickup| match p {
+            Pickup::EternalHeart => 2,
+            Pickup::Rune => 1,
+            _ => 0,
+        };
+        assert_eq!(
+            vec![
+                (ItemPool::Treasure, 1.),
+                (ItemPool::Angel, 20.),
+                (ItemPool::Planetarium, 10.)
+            ],
+            rules.pool_weights(counts)
+        );
+        assert_eq!(
+            vec![(ItemPool::Treasure, 1.), (ItemPool::Angel, 0.)],
+            rules.pool_weights(|p| (p == Pickup::Penny) as u32)
+        );
+        assert_eq!(2..=4, rules.quality_bounds(ItemPool::Angel, 11));
+        assert_eq!(0..=0, rules.quality_bounds(ItemPool::Angel, 10));
+    }
+
+    #[test]
+    fn default_quality_offsets() {
+        let rules = CraftingRules::default();
+        assert_eq!(4..=4, rules.quality_bounds(ItemPool::Treasure, 35));
+        assert_eq!(2..=4, rules.quality_bounds(ItemPool::Devil, 35));
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{CrafterError, InternalItemId, ItemCatalog, UnlockProfile};
+
+const HEADER_LENGTH: usize = 16;
+const MAGIC_LENGTH: usize = 14;
+/// The header is followed by 4 bytes we don't need before the first section.
+const FIRST_SECTION_OFFSET: usize = HEADER_LENGTH + 4;
+const ACHIEVEMENTS_SECTION: u32 = 1;
+/// The file ends with a checksum of everything after the header.
+const CHECKSUM_LENGTH: usize = 4;
+const CHECKSUM_SEED: u32 = 0xFEDCBA76;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SaveVersion {
+    Afterbirth,
+    AfterbirthPlus,
+    Repentance,
+}
+
+impl SaveVersion {
+    fn magic(self) -> &'static [u8; MAGIC_LENGTH] {
+        match self {
+            SaveVersion::Afterbirth => b"ISAACNGSAVE06R",
+            SaveVersion::AfterbirthPlus => b"ISAACNGSAVE08R",
+            SaveVersion::Repentance => b"ISAACNGSAVE09R",
+        }
+    }
+
+    fn from_magic(magic: &[u8]) -> Option<Self> {
+        [
+            SaveVersion::Afterbirth,
+            SaveVersion::AfterbirthPlus,
+            SaveVersion::Repentance,
+        ]
+        .iter()
+        .copied()
+        .find(|version| &version.magic()[..] == magic)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SaveFileError {
+    /// The file ends at `len` bytes but `needed` bytes are required to read it.
+    Truncated { needed: usize, len: usize },
+    /// The header doesn't belong to any save format we know.
+    UnknownVersion(String),
+    /// The first section should hold the achievements, but has another type.
+    UnexpectedSection { expected: u32, found: u32 },
+    /// The checksum at the end of the file doesn't match its contents.
+    Checksum { expected: u32, found: u32 },
+}
+
+impl Display for SaveFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveFileError::Truncated { needed, len } => write!(
+                f,
+                "save file is truncated, it has {} bytes but at least {} are needed",
+                len, needed
+            ),
+            SaveFileError::UnknownVersion(header) => {
+                write!(f, "unknown save file version {:?}", header)
+            }
+            SaveFileError::UnexpectedSection { expected, found } => write!(
+                f,
+                "expected save file section {} but found section {}",
+                expected, found
+            ),
+            SaveFileError::Checksum { expected, found } => write!(
+                f,
+                "save file checksum is {:08x} but its contents add up to {:08x}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveFileError {}
+
+/// The parts of a `persistentgamedata*.dat` save file the crafter cares about.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SaveFile {
+    pub version: SaveVersion,
+    /// Indexed by achievement id, achievement 0 doesn't exist.
+    achievements: Vec<bool>,
+}
+
+impl SaveFile {
+    pub fn parse(data: &[u8]) -> Result<Self, SaveFileError> {
+        let header = read(data, 0, HEADER_LENGTH)?;
+        let version = SaveVersion::from_magic(&header[..MAGIC_LENGTH]).ok_or_else(|| {
+            SaveFileError::UnknownVersion(String::from_utf8_lossy(header).trim_end().to_owned())
+        })?;
+
+        // every section starts with 3 u32s: its type, then one we don't need, then its number of
+        // entries. The achievements section comes first and has a byte per achievement.
+        let section_header = read(data, FIRST_SECTION_OFFSET, 12)?;
+        let section = read_u32(section_header, 0);
+        if section != ACHIEVEMENTS_SECTION {
+            return Err(SaveFileError::UnexpectedSection {
+                expected: ACHIEVEMENTS_SECTION,
+                found: section,
+            });
+        }
+        let count = read_u32(section_header, 8) as usize;
+        let achievements = read(data, FIRST_SECTION_OFFSET + 12, count)?
+            .iter()
+            .map(|flag| *flag != 0)
+            .collect();
+
+        let checksum_offset = data
+            .len()
+            .saturating_sub(CHECKSUM_LENGTH)
+            .max(HEADER_LENGTH);
+        let found = read_u32(read(data, checksum_offset, CHECKSUM_LENGTH)?, 0);
+        let expected = checksum(&data[HEADER_LENGTH..checksum_offset]);
+        if found != expected {
+            return Err(SaveFileError::Checksum { expected, found });
+        }
+
+        Ok(SaveFile {
+            version,
+            achievements,
+        })
+    }
+
+    pub fn is_achievement_unlocked(&self, achievement: u16) -> bool {
+        self.achievements
+            .get(achievement as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Lock every item whose achievement this save hasn't unlocked.
+    pub fn unlock_profile(&self, table: &AchievementTable) -> UnlockProfile {
+        table
+            .items
+            .iter()
+            .filter(|(achievement, _)| !self.is_achievement_unlocked(**achievement))
+            .flat_map(|(_, items)| items.iter().copied())
+            .collect()
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// A CRC-32 starting from the game's own seed instead of all ones.
+fn checksum(data: &[u8]) -> u32 {
+    let mut crc = !CHECKSUM_SEED;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn read(data: &[u8], offset: usize, len: usize) -> Result<&[u8], SaveFileError> {
+    data.get(offset..offset + len)
+        .ok_or(SaveFileError::Truncated {
+            needed: offset + len,
+            len: data.len(),
+        })
+}
+
+/// Which collectibles each achievement unlocks, read from the `achievement` attribute of the
+/// collectibles in the game's items.xml.
+#[derive(Debug, Clone, Default)]
+pub struct AchievementTable {
+    items: HashMap<u16, Vec<InternalItemId>>,
+}
+
+impl AchievementTable {
+    pub fn from_items_xml(items_xml: &str) -> Result<Self, CrafterError> {
+        Ok(AchievementTable::from_catalog(&ItemCatalog::new(
+            items_xml,
+        )?))
+    }
+
+    pub fn from_catalog(catalog: &ItemCatalog) -> Self {
+        let mut table = AchievementTable::default();
+        for item in catalog.items() {
+            if let Some(achievement) = item.achievement {
+                table.insert(achievement, item.id);
+            }
+        }
+        table
+    }
+
+    pub fn insert(&mut self, achievement: u16, item: InternalItemId) {
+        self.items.entry(achievement).or_default().push(item);
+    }
+}
+
+#[wasm_bindgen]
+impl UnlockProfile {
+    /// Read the unlocks of a `persistentgamedata*.dat` save file, using the game's items.xml to know
+    /// which items each achievement unlocks.
+    pub fn from_save_file(
+        save_file: &[u8],
+        items_xml: &str,
+    ) -> Result<UnlockProfile, CrafterError> {
+        let save_file = SaveFile::parse(save_file)?;
+        Ok(save_file.unlock_profile(&AchievementTable::from_items_xml(items_xml)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS_XML: &str = include_str!("../fixtures/items.xml");
+    const REPENTANCE_SAVE: &[u8] = include_bytes!("../fixtures/persistentgamedata_repentance.dat");
+
+    #[test]
+    fn parse_repentance() {
+        let save_file = SaveFile::parse(REPENTANCE_SAVE).unwrap();
+        assert_eq!(SaveVersion::Repentance, save_file.version);
+        assert!(!save_file.is_achievement_unlocked(0));
+        assert!(save_file.is_achievement_unlocked(4));
+        assert!(save_file.is_achievement_unlocked(10));
+        assert!(!save_file.is_achievement_unlocked(12));
+        assert!(!save_file.is_achievement_unlocked(637));
+    }
+
+    #[test]
+    fn locked_items() {
+        let save_file = SaveFile::parse(REPENTANCE_SAVE).unwrap();
+        let table = AchievementTable::from_items_xml(ITEMS_XML).unwrap();
+        let unlocks = save_file.unlock_profile(&table);
+        let mut locked: Vec<InternalItemId> = unlocks.locked_items().collect();
+        locked.sort();
+        assert_eq!(
+            vec![
+                InternalItemId(33),
+                InternalItemId(67),
+                InternalItemId(105),
+                InternalItemId(182)
+            ],
+            locked
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            Err(SaveFileError::Truncated {
+                needed: 52,
+                len: 40
+            }),
+            SaveFile::parse(include_bytes!(
+                "../fixtures/persistentgamedata_truncated.dat"
+            ))
+        );
+        assert_eq!(
+            Err(SaveFileError::Truncated { needed: 16, len: 3 }),
+            SaveFile::parse(b"ISA")
+        );
+    }
+
+    #[test]
+    fn unexpected_section() {
+        let mut data = REPENTANCE_SAVE.to_vec();
+        data[FIRST_SECTION_OFFSET] = 2;
+        assert_eq!(
+            Err(SaveFileError::UnexpectedSection {
+                expected: 1,
+                found: 2
+            }),
+            SaveFile::parse(&data)
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut data = REPENTANCE_SAVE.to_vec();
+        // lock achievement 4
+        data[FIRST_SECTION_OFFSET + 12 + 4] = 0;
+        assert_eq!(
+            Err(SaveFileError::Checksum {
+                expected: checksum(&data[HEADER_LENGTH..data.len() - 4]),
+                found: 0xd603beec
+            }),
+            SaveFile::parse(&data)
+        );
+    }
+
+    #[test]
+    fn unknown_version() {
+        assert_eq!(
+            Err(SaveFileError::UnknownVersion("ISAACNGSAVE99R".to_owned())),
+            SaveFile::parse(include_bytes!(
+                "../fixtures/persistentgamedata_unknown_version.dat"
+            ))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt::{Display, Formatter};
+
+use wasm_bindgen::prelude::*;
+
+const SEED_CHARS: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTWXYZ01234V6789";
+const SEED_LENGTH: usize = 8;
+/// The game scrambles the seed with this before packing it into characters.
+const SEED_XOR: u32 = 0x0FEF7FFD;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SeedParseError {
+    /// The seed doesn't have 8 characters, ignoring whitespace.
+    Length(usize),
+    /// The character isn't part of the seed alphabet.
+    InvalidCharacter(char),
+    /// Every character is valid but the checksum doesn't match the seed.
+    Checksum,
+}
+
+impl Display for SeedParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeedParseError::Length(len) => {
+                write!(f, "a seed has {} characters, found {}", SEED_LENGTH, len)
+            }
+            SeedParseError::InvalidCharacter(c) => {
+                write!(f, "{:?} can't appear in a seed", c)
+            }
+            SeedParseError::Checksum => write!(f, "seed checksum doesn't match"),
+        }
+    }
+}
+
+impl std::error::Error for SeedParseError {}
+
+fn checksum(seed: u32) -> u8 {
+    let mut checksum = 0_u8;
+    let mut remaining = seed;
+    while remaining != 0 {
+        checksum = checksum.wrapping_add(remaining as u8);
+        checksum = checksum.rotate_left(1);
+        remaining >>= 5;
+    }
+    checksum
+}
+
+/// Parse a seed the way the game displays it, e.g. `"ABCD 1234"`. Case and whitespace are ignored.
+pub fn parse_seed(seed: &str) -> Result<u32, SeedParseError> {
+    let chars: Vec<char> = seed
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if chars.len() != SEED_LENGTH {
+        return Err(SeedParseError::Length(chars.len()));
+    }
+
+    let mut packed = 0_u64;
+    for c in chars {
+        let value = SEED_CHARS
+            .iter()
+            .position(|s| *s as char == c)
+            .ok_or(SeedParseError::InvalidCharacter(c))?;
+        packed = (packed << 5) | value as u64;
+    }
+
+    let seed = (packed >> 8) as u32 ^ SEED_XOR;
+    if checksum(seed) != packed as u8 {
+        return Err(SeedParseError::Checksum);
+    }
+    Ok(seed)
+}
+
+/// Format a seed the way the game displays it, e.g. `"ABCD 1234"`.
+pub fn format_seed(seed: u32) -> String {
+    let packed = (((seed ^ SEED_XOR) as u64) << 8) | checksum(seed) as u64;
+    let mut result = String::with_capacity(SEED_LENGTH + 1);
+    for i in 0..SEED_LENGTH {
+        if i == SEED_LENGTH / 2 {
+            result.push(' ');
+        }
+        let shift = 5 * (SEED_LENGTH - 1 - i);
+        result.push(SEED_CHARS[((packed >> shift) & 0x1F) as usize] as char);
+    }
+    result
+}
+
+#[wasm_bindgen(js_name = parse_seed)]
+pub fn js_parse_seed(seed: &str) -> Result<u32, JsValue> {
+    parse_seed(seed).map_err(|e| JsValue::from(e.to_string()))
+}
+
+#[wasm_bindgen(js_name = format_seed)]
+pub fn js_format_seed(seed: u32) -> String {
+    format_seed(seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_seeds() {
+        // worked out apart from this codec, from the game's xor, packing and checksum
+        let known = [
+            (1_u32, "B911 99AC"),
+            (0x12345678, "D1PW XBN9"),
+            (3040223013, "1MN0 B0DN"),
+            (u32::MAX, "8AJJ AAYH"),
+        ];
+        for (seed, formatted) in known.iter() {
+            assert_eq!(*formatted, format_seed(*seed));
+            assert_eq!(Ok(*seed), parse_seed(formatted));
+        }
+        // the easter egg seeds are recognized by name and don't have a valid checksum
+        assert_eq!(Err(SeedParseError::Checksum), parse_seed("KEEP AWAY"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in [0_u32, 1, 0x77777770, 0x12345678, u32::MAX].iter().copied() {
+            let formatted = format_seed(seed);
+            assert_eq!(9, formatted.len());
+            assert_eq!(Ok(seed), parse_seed(&formatted));
+            assert_eq!(
+                Ok(seed),
+                parse_seed(&formatted.replace(' ', "").to_ascii_lowercase())
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Err(SeedParseError::Length(7)), parse_seed("ABCD 123"));
+        assert_eq!(
+            Err(SeedParseError::InvalidCharacter('I')),
+            parse_seed("ABCD 123I")
+        );
+
+        let mut formatted = format_seed(0x12345678).into_bytes();
+        let last = formatted.len() - 1;
+        formatted[last] = if formatted[last] == b'A' { b'B' } else { b'A' };
+        assert_eq!(
+            Err(SeedParseError::Checksum),
+            parse_seed(std::str::from_utf8(&formatted).unwrap())
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+use std::iter::FromIterator;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{js_item_id_to_item_id, CrafterError, InternalItemId, ItemId};
+
+/// The items a save file hasn't unlocked yet. Locked items never come out of a pool.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct UnlockProfile {
+    locked: HashSet<InternalItemId>,
+}
+
+#[wasm_bindgen]
+impl UnlockProfile {
+    /// A profile with everything unlocked.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> UnlockProfile {
+        Default::default()
+    }
+
+    pub fn lock(&mut self, item: ItemId) -> Result<(), CrafterError> {
+        self.lock_item(js_item_id_to_item_id(item)?);
+        Ok(())
+    }
+
+    pub fn unlock(&mut self, item: ItemId) -> Result<(), CrafterError> {
+        self.unlock_item(js_item_id_to_item_id(item)?);
+        Ok(())
+    }
+
+    pub fn is_locked(&self, item: ItemId) -> Result<bool, CrafterError> {
+        Ok(self.is_item_locked(js_item_id_to_item_id(item)?))
+    }
+}
+
+impl UnlockProfile {
+    pub fn lock_item(&mut self, item: InternalItemId) {
+        self.locked.insert(item);
+    }
+
+    pub fn unlock_item(&mut self, item: InternalItemId) {
+        self.locked.remove(&item);
+    }
+
+    pub fn is_item_locked(&self, item: InternalItemId) -> bool {
+        self.locked.contains(&item)
+    }
+
+    pub fn locked_items(&self) -> impl Iterator<Item = InternalItemId> + '_ {
+        self.locked.iter().copied()
+    }
+}
+
+impl FromIterator<InternalItemId> for UnlockProfile {
+    fn from_iter<T: IntoIterator<Item = InternalItemId>>(iter: T) -> Self {
+        UnlockProfile {
+            locked: iter.into_iter().collect(),
+        }
+    }
+}
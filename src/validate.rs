@@ -0,0 +1,371 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use strum::IntoEnumIterator;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    resolve_item_id, CrafterError, GameData, InternalItemId, ItemPool, ItemPools, ItemsMetadata,
+};
+
+/// A mistake in itempools.xml or items_metadata.xml that makes the crafter give wrong answers
+/// without failing to load, or that stops it from loading at all.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// itempools.xml has a pool the game doesn't.
+    UnknownPool(String),
+    /// itempools.xml doesn't have one of the game's pools.
+    MissingPool(ItemPool),
+    /// itempools.xml lists the same pool twice.
+    DuplicatePool(ItemPool),
+    /// A pool lists the same item twice.
+    DuplicatePoolItem { pool: String, item: InternalItemId },
+    /// A pool has an item that isn't in items_metadata.xml, so it has quality 0 and no tags.
+    ItemWithoutMetadata { pool: String, item: InternalItemId },
+    /// A pool has an item that can never be rolled, or whose weight isn't a number.
+    NonPositiveWeight {
+        pool: String,
+        item: InternalItemId,
+        weight: f32,
+    },
+    /// Something that should be an item id isn't one.
+    InvalidItemId { file: &'static str, id: String },
+    /// A mod's file refers to an item by a name no mod item has.
+    UnknownItemName { file: &'static str, name: String },
+    /// items_metadata.xml lists the same item twice.
+    DuplicateItem(InternalItemId),
+    /// An item in items_metadata.xml has no quality.
+    MissingQuality(InternalItemId),
+}
+
+impl Diagnostic {
+    /// The diagnostic for an entry [`resolve_item_id`] failed on.
+    fn unresolved(file: &'static str, id: Option<&str>, error: CrafterError) -> Self {
+        match error {
+            CrafterError::UnknownItemName(name) => Diagnostic::UnknownItemName { file, name },
+            _ => Diagnostic::InvalidItemId {
+                file,
+                id: id.unwrap_or_default().to_owned(),
+            },
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::UnknownPool(pool) => write!(f, "unknown item pool {:?}", pool),
+            Diagnostic::MissingPool(pool) => write!(f, "the {} pool is missing", pool.name()),
+            Diagnostic::DuplicatePool(pool) => {
+                write!(f, "the {} pool is listed twice", pool.name())
+            }
+            Diagnostic::DuplicatePoolItem { pool, item } => write!(
+                f,
+                "item {} is listed twice in the {} pool",
+                u16::from(*item),
+                pool
+            ),
+            Diagnostic::ItemWithoutMetadata { pool, item } => write!(
+                f,
+                "item {} of the {} pool has no metadata",
+                u16::from(*item),
+                pool
+            ),
+            Diagnostic::NonPositiveWeight { pool, item, weight } => write!(
+                f,
+                "item {} of the {} pool has weight {}",
+                u16::from(*item),
+                pool,
+                weight
+            ),
+            Diagnostic::InvalidItemId { file, id } => {
+                write!(f, "{:?} in {} is not a valid item id", id, file)
+            }
+            Diagnostic::UnknownItemName { file, name } => {
+                write!(f, "{:?} in {} is not the name of a mod item", name, file)
+            }
+            Diagnostic::DuplicateItem(item) => write!(
+                f,
+                "item {} is listed twice in the item metadata",
+                u16::from(*item)
+            ),
+            Diagnostic::MissingQuality(item) => {
+                write!(f, "item {} has no quality", u16::from(*item))
+            }
+        }
+    }
+}
+
+/// Check itempools.xml and items_metadata.xml for mistakes, in the order they appear in the files.
+///
+/// Only files that aren't xml or aren't laid out like the game's files are an error, everything
+/// else is reported as a [`Diagnostic`].
+pub fn validate_game_data(
+    itempools_xml: &str,
+    items_metadata_xml: &str,
+) -> Result<Vec<Diagnostic>, CrafterError> {
+    validate(itempools_xml, items_metadata_xml, None)
+}
+
+/// [`validate_game_data`] for a mod's `content/itempools.xml` and `content/items_metadata.xml`,
+/// before merging them into `data`. Entries are resolved like [`GameData::merge_itempools`] does,
+/// so the mod's items.xml has to be merged first. Items only need metadata somewhere in `data` or
+/// the mod, and the mod doesn't need to have every pool.
+pub fn validate_mod_data(
+    data: &GameData,
+    itempools_xml: &str,
+    items_metadata_xml: &str,
+) -> Result<Vec<Diagnostic>, CrafterError> {
+    validate(itempools_xml, items_metadata_xml, Some(data))
+}
+
+fn validate(
+    itempools_xml: &str,
+    items_metadata_xml: &str,
+    base: Option<&GameData>,
+) -> Result<Vec<Diagnostic>, CrafterError> {
+    let pools: ItemPools = serde_xml_rs::from_reader(itempools_xml.as_bytes())
+        .map_err(|e| CrafterError::from_xml("itempools.xml", e))?;
+    let metadata: ItemsMetadata = serde_xml_rs::from_reader(items_metadata_xml.as_bytes())
+        .map_err(|e| CrafterError::from_xml("items_metadata.xml", e))?;
+    let no_names = HashMap::new();
+    let names = base.map_or(&no_names, |data| &data.mod_items);
+    let mut diagnostics = Vec::new();
+
+    let mut with_metadata = HashSet::new();
+    for item in metadata.items.iter() {
+        let id = match resolve_item_id(item.id.as_deref(), item.name.as_deref(), names) {
+            Ok(id) => id,
+            Err(error) => {
+                diagnostics.push(Diagnostic::unresolved(
+                    "items_metadata.xml",
+                    item.id.as_deref(),
+                    error,
+                ));
+                continue;
+            }
+        };
+        if !with_metadata.insert(id) {
+            diagnostics.push(Diagnostic::DuplicateItem(id));
+        }
+        if item.quality.is_none() {
+            diagnostics.push(Diagnostic::MissingQuality(id));
+        }
+    }
+    let has_metadata = |id| {
+        with_metadata.contains(&id) || base.is_some_and(|data| data.metadata.contains_key(&id))
+    };
+
+    let item_pool_lookup: HashMap<&'static str, ItemPool> =
+        ItemPool::iter().map(|pool| (pool.name(), pool)).collect();
+    let mut seen_pools = HashSet::new();
+    for pool in pools.pools.iter() {
+        match item_pool_lookup.get(pool.name.as_str()) {
+            Some(item_pool) => {
+                if !seen_pools.insert(*item_pool) {
+                    diagnostics.push(Diagnostic::DuplicatePool(*item_pool));
+                }
+            }
+            None => diagnostics.push(Diagnostic::UnknownPool(pool.name.to_string())),
+        }
+
+        let mut in_pool = HashSet::new();
+        for item in pool.items.iter() {
+            let id = match resolve_item_id(item.id.as_deref(), item.name.as_deref(), names) {
+                Ok(id) => id,
+                Err(error) => {
+                    diagnostics.push(Diagnostic::unresolved(
+                        "itempools.xml",
+                        item.id.as_deref(),
+                        error,
+                    ));
+                    continue;
+                }
+            };
+            if !in_pool.insert(id) {
+                diagnostics.push(Diagnostic::DuplicatePoolItem {
+                    pool: pool.name.to_string(),
+                    item: id,
+                });
+            }
+            if !has_metadata(id) {
+                diagnostics.push(Diagnostic::ItemWithoutMetadata {
+                    pool: pool.name.to_string(),
+                    item: id,
+                });
+            }
+            if !item.weight.is_finite() || item.weight <= 0. {
+                diagnostics.push(Diagnostic::NonPositiveWeight {
+                    pool: pool.name.to_string(),
+                    item: id,
+                    weight: item.weight,
+                });
+            }
+        }
+    }
+    if base.is_none() {
+        diagnostics.extend(
+            ItemPool::iter()
+                .filter(|pool| !seen_pools.contains(pool))
+                .map(Diagnostic::MissingPool),
+        );
+    }
+
+    Ok(diagnostics)
+}
+
+/// [`validate_game_data`], with each diagnostic as a message.
+#[wasm_bindgen(js_name = validate_game_data)]
+pub fn js_validate_game_data(
+    itempools_xml: &str,
+    items_metadata_xml: &str,
+) -> Result<Vec<String>, CrafterError> {
+    Ok(validate_game_data(itempools_xml, items_metadata_xml)?
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect())
+}
+
+/// [`validate_mod_data`], with each diagnostic as a message.
+#[wasm_bindgen(js_name = validate_mod_data)]
+pub fn js_validate_mod_data(
+    data: &GameData,
+    itempools_xml: &str,
+    items_metadata_xml: &str,
+) -> Result<Vec<String>, CrafterError> {
+    Ok(validate_mod_data(data, itempools_xml, items_metadata_xml)?
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMPOOLS_XML: &str = include_str!("../web/assets/itempools.xml");
+    const ITEMS_METADATA_XML: &str = include_str!("../web/assets/items_metadata.xml");
+    const MOD_ITEMS_XML: &str = include_str!("../fixtures/mod/content/items.xml");
+    const MOD_ITEMPOOLS_XML: &str = include_str!("../fixtures/mod/content/itempools.xml");
+    const MOD_ITEMS_METADATA_XML: &str = include_str!("../fixtures/mod/content/items_metadata.xml");
+
+    #[test]
+    fn game_files() {
+        assert_eq!(
+            Vec::<Diagnostic>::new(),
+            validate_game_data(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap()
+        );
+    }
+
+    #[test]
+    fn diagnostics() {
+        let diagnostics = validate_game_data(
+            r#"<ItemPools>
+                <Pool Name="treasure">
+                    <Item Id="1" Weight="1"/>
+                    <Item Id="1" Weight="1"/>
+                    <Item Id="2" Weight="0"/>
+                    <Item Id="900" Weight="1"/>
+                    <Item Id="-3" Weight="1"/>
+                </Pool>
+                <Pool Name="moddedPool">
+                    <Item Id="1" Weight="1"/>
+                </Pool>
+                <Pool Name="treasure">
+                    <Item Id="1" Weight="1"/>
+                </Pool>
+            </ItemPools>"#,
+            r#"<items>
+                <item id="1" quality="3"/>
+                <item id="2"/>
+                <item id="2" quality="1"/>
+            </items>"#,
+        )
+        .unwrap();
+
+        let mut expected = vec![
+            Diagnostic::MissingQuality(InternalItemId(2)),
+            Diagnostic::DuplicateItem(InternalItemId(2)),
+            Diagnostic::DuplicatePoolItem {
+                pool: "treasure".to_owned(),
+                item: InternalItemId(1),
+            },
+            Diagnostic::NonPositiveWeight {
+                pool: "treasure".to_owned(),
+                item: InternalItemId(2),
+                weight: 0.,
+            },
+            Diagnostic::ItemWithoutMetadata {
+                pool: "treasure".to_owned(),
+                item: InternalItemId(900),
+            },
+            Diagnostic::InvalidItemId {
+                file: "itempools.xml",
+                id: "-3".to_owned(),
+            },
+            Diagnostic::UnknownPool("moddedPool".to_owned()),
+            Diagnostic::DuplicatePool(ItemPool::Treasure),
+        ];
+        expected.extend(
+            ItemPool::iter()
+                .filter(|pool| *pool != ItemPool::Treasure)
+                .map(Diagnostic::MissingPool),
+        );
+        assert_eq!(expected, diagnostics);
+        assert_eq!(
+            "item 900 of the treasure pool has no metadata",
+            diagnostics[4].to_string()
+        );
+
+        // NaN isn't below or equal to 0 either, but it can't be rolled
+        let diagnostics = validate_game_data(
+            r#"<ItemPools><Pool Name="treasure"><Item Id="1" Weight="nan"/></Pool></ItemPools>"#,
+            r#"<items><item id="1" quality="3"/></items>"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            diagnostics[0],
+            Diagnostic::NonPositiveWeight { item: InternalItemId(1), weight, .. } if weight.is_nan()
+        ));
+    }
+
+    #[test]
+    fn mod_files() {
+        let mut data = GameData::new(ITEMPOOLS_XML, ITEMS_METADATA_XML).unwrap();
+        data.merge_items(MOD_ITEMS_XML).unwrap();
+        assert_eq!(
+            Vec::<Diagnostic>::new(),
+            validate_mod_data(&data, MOD_ITEMPOOLS_XML, MOD_ITEMS_METADATA_XML).unwrap()
+        );
+
+        let diagnostics = validate_mod_data(
+            &data,
+            r#"<ItemPools>
+                <Pool Name="shop">
+                    <Item Name="Lucky Onion" Weight="1"/>
+                    <Item Name="Sad Onion" Weight="1"/>
+                    <Item Weight="1"/>
+                </Pool>
+            </ItemPools>"#,
+            r#"<items><item name="Brother Rock" quality="1"/></items>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                Diagnostic::ItemWithoutMetadata {
+                    pool: "shop".to_owned(),
+                    item: InternalItemId(730),
+                },
+                Diagnostic::UnknownItemName {
+                    file: "itempools.xml",
+                    name: "Sad Onion".to_owned(),
+                },
+                Diagnostic::InvalidItemId {
+                    file: "itempools.xml",
+                    id: String::new(),
+                },
+            ],
+            diagnostics
+        );
+    }
+}
@@ -10,6 +10,11 @@ export type PickupImageParams = {
   classes?: string,
 }
 
+/** Pickups added to the game after the images were made have none yet. */
+export function has_pickup_image(pickup: TPickup): boolean {
+  return `${pickup}.png` in images
+}
+
 export function PickupImage({ pickup, classes }: PickupImageParams): React.ReactElement {
   return (
     <img className={`object-contain pr-3 ${string_or_empty(classes)}`} src={images[`${pickup}.png`]}
@@ -1,6 +1,7 @@
 import * as React from 'react'
 import { Pickup as TPickup } from '../../pkg'
 import { Pickup } from './pickup'
+import { has_pickup_image } from './pickup/pickup_image'
 import { string_or_empty } from '../util/defaults'
 
 export interface PickupsParams {
@@ -15,6 +16,7 @@ export function Pickups({ class_names }: PickupsParams = {}): React.ReactElement
       {Object.values(TPickup)
         .filter(key => isNaN(Number(key)))
         .map(k => k as TPickup)
+        .filter(has_pickup_image)
         .map(pickup => (
           <Pickup key={pickup} pickup={pickup} />
         ))}